@@ -0,0 +1,137 @@
+/// Characters that case fold to another character without being that character's uppercase form,
+/// paired with the character they fold to
+/// These can't be found from the folded character with to_uppercase, e.g. both 'ς' and 'σ' uppercase to 'Σ'
+/// but 'σ' is the only lowercase form of 'Σ'
+const OTHER_CASE_FORMS: [(char, char); 58] = [
+    ('\u{b5}', '\u{3bc}'),    // µ μ
+    ('\u{17f}', '\u{73}'),    // ſ s
+    ('\u{1c5}', '\u{1c6}'),   // ǅ ǆ
+    ('\u{1c8}', '\u{1c9}'),   // ǈ ǉ
+    ('\u{1cb}', '\u{1cc}'),   // ǋ ǌ
+    ('\u{1f2}', '\u{1f3}'),   // ǲ ǳ
+    ('\u{345}', '\u{3b9}'),   // ͅ ι
+    ('\u{3c2}', '\u{3c3}'),   // ς σ
+    ('\u{3d0}', '\u{3b2}'),   // ϐ β
+    ('\u{3d1}', '\u{3b8}'),   // ϑ θ
+    ('\u{3d5}', '\u{3c6}'),   // ϕ φ
+    ('\u{3d6}', '\u{3c0}'),   // ϖ π
+    ('\u{3f0}', '\u{3ba}'),   // ϰ κ
+    ('\u{3f1}', '\u{3c1}'),   // ϱ ρ
+    ('\u{3f4}', '\u{3b8}'),   // ϴ θ
+    ('\u{3f5}', '\u{3b5}'),   // ϵ ε
+    ('\u{1c80}', '\u{432}'),  // ᲀ в
+    ('\u{1c81}', '\u{434}'),  // ᲁ д
+    ('\u{1c82}', '\u{43e}'),  // ᲂ о
+    ('\u{1c83}', '\u{441}'),  // ᲃ с
+    ('\u{1c84}', '\u{442}'),  // ᲄ т
+    ('\u{1c85}', '\u{442}'),  // ᲅ т
+    ('\u{1c86}', '\u{44a}'),  // ᲆ ъ
+    ('\u{1c87}', '\u{463}'),  // ᲇ ѣ
+    ('\u{1c88}', '\u{a64b}'), // ᲈ ꙋ
+    ('\u{1e9b}', '\u{1e61}'), // ẛ ṡ
+    ('\u{1e9e}', '\u{df}'),   // ẞ ß
+    ('\u{1f88}', '\u{1f80}'), // ᾈ ᾀ
+    ('\u{1f89}', '\u{1f81}'), // ᾉ ᾁ
+    ('\u{1f8a}', '\u{1f82}'), // ᾊ ᾂ
+    ('\u{1f8b}', '\u{1f83}'), // ᾋ ᾃ
+    ('\u{1f8c}', '\u{1f84}'), // ᾌ ᾄ
+    ('\u{1f8d}', '\u{1f85}'), // ᾍ ᾅ
+    ('\u{1f8e}', '\u{1f86}'), // ᾎ ᾆ
+    ('\u{1f8f}', '\u{1f87}'), // ᾏ ᾇ
+    ('\u{1f98}', '\u{1f90}'), // ᾘ ᾐ
+    ('\u{1f99}', '\u{1f91}'), // ᾙ ᾑ
+    ('\u{1f9a}', '\u{1f92}'), // ᾚ ᾒ
+    ('\u{1f9b}', '\u{1f93}'), // ᾛ ᾓ
+    ('\u{1f9c}', '\u{1f94}'), // ᾜ ᾔ
+    ('\u{1f9d}', '\u{1f95}'), // ᾝ ᾕ
+    ('\u{1f9e}', '\u{1f96}'), // ᾞ ᾖ
+    ('\u{1f9f}', '\u{1f97}'), // ᾟ ᾗ
+    ('\u{1fa8}', '\u{1fa0}'), // ᾨ ᾠ
+    ('\u{1fa9}', '\u{1fa1}'), // ᾩ ᾡ
+    ('\u{1faa}', '\u{1fa2}'), // ᾪ ᾢ
+    ('\u{1fab}', '\u{1fa3}'), // ᾫ ᾣ
+    ('\u{1fac}', '\u{1fa4}'), // ᾬ ᾤ
+    ('\u{1fad}', '\u{1fa5}'), // ᾭ ᾥ
+    ('\u{1fae}', '\u{1fa6}'), // ᾮ ᾦ
+    ('\u{1faf}', '\u{1fa7}'), // ᾯ ᾧ
+    ('\u{1fbc}', '\u{1fb3}'), // ᾼ ᾳ
+    ('\u{1fbe}', '\u{3b9}'),  // ι ι
+    ('\u{1fcc}', '\u{1fc3}'), // ῌ ῃ
+    ('\u{1ffc}', '\u{1ff3}'), // ῼ ῳ
+    ('\u{2126}', '\u{3c9}'),  // Ω ω
+    ('\u{212a}', '\u{6b}'),   // K k
+    ('\u{212b}', '\u{e5}'),   // Å å
+];
+
+/// The character if the iterator yields exactly one character
+fn single_char(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    match (chars.next(), chars.next()) {
+        (Some(char), None) => Some(char),
+        _ => None,
+    }
+}
+
+/// Unicode simple case folding, which maps every case form of a character to the same character
+/// Characters whose case mapping is more than one character, such as 'ß' to "SS", are kept as they are
+pub fn fold_case(character: char) -> char {
+    // the dotless 'ı' uppercases to 'I', but like 'İ' it only folds to 'i' in Turkish
+    if character == 'ı' {
+        return character;
+    }
+    let upper = single_char(character.to_uppercase()).unwrap_or(character);
+    single_char(upper.to_lowercase()).unwrap_or(upper)
+}
+
+/// Every character that case folds to the same character as `character`, including itself
+pub fn case_forms(character: char) -> impl Iterator<Item = char> {
+    let folded = fold_case(character);
+    let other_forms = OTHER_CASE_FORMS
+        .iter()
+        .filter(move |(_, other_folded)| *other_folded == folded)
+        .map(|(other, _)| *other);
+    [
+        Some(character),
+        Some(folded),
+        single_char(folded.to_uppercase()),
+    ]
+    .into_iter()
+    .flatten()
+    .chain(other_forms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_case() {
+        assert_eq!(fold_case('A'), 'a');
+        assert_eq!(fold_case('a'), 'a');
+        assert_eq!(fold_case('Σ'), 'σ');
+        assert_eq!(fold_case('ς'), 'σ');
+        assert_eq!(fold_case('\u{212A}'), 'k'); // Kelvin sign
+        assert_eq!(fold_case('ß'), 'ß');
+        assert_eq!(fold_case('ı'), 'ı');
+        assert_eq!(fold_case('1'), '1');
+    }
+
+    #[test]
+    fn test_case_forms() {
+        let mut forms: Vec<char> = case_forms('σ').collect();
+        forms.sort();
+        forms.dedup();
+        assert_eq!(forms, vec!['Σ', 'ς', 'σ']);
+
+        let mut forms: Vec<char> = case_forms('K').collect();
+        forms.sort();
+        forms.dedup();
+        assert_eq!(forms, vec!['K', 'k', '\u{212A}']);
+    }
+
+    #[test]
+    fn test_other_case_forms_fold() {
+        for (other, folded) in OTHER_CASE_FORMS {
+            assert_eq!(fold_case(other), folded);
+        }
+    }
+}
@@ -14,6 +14,10 @@ impl<const N: usize> CharacterClass for CharacterGroupClass<N> {
     fn matches(&self, character: char) -> bool {
         self.valid_chars.contains(&character)
     }
+
+    fn describe(&self) -> String {
+        describe_chars(&self.valid_chars)
+    }
 }
 
 impl<const N: usize> CharacterGroupClass<N> {
@@ -22,7 +26,7 @@ impl<const N: usize> CharacterGroupClass<N> {
             valid_chars: self
                 .valid_chars
                 .into_iter()
-                .chain(other.valid_chars.into_iter())
+                .chain(other.valid_chars)
                 .collect(),
         }
     }
@@ -32,6 +36,33 @@ impl CharacterClass for CharacterGroupClassBoxed {
     fn matches(&self, character: char) -> bool {
         self.valid_chars.contains(&character)
     }
+
+    fn describe(&self) -> String {
+        describe_chars(&self.valid_chars)
+    }
+}
+
+/// Describe the chars like a bracket expression, abbreviating runs of consecutive chars as ranges
+fn describe_chars(chars: &[char]) -> String {
+    let mut description = String::from("[");
+    let mut remaining = chars;
+    while let [first, ..] = remaining {
+        let run_length = remaining
+            .iter()
+            .zip(*first..)
+            .take_while(|(char, expected)| *char == expected)
+            .count();
+        if run_length >= 3 {
+            description.push(*first);
+            description.push('-');
+            description.push(remaining[run_length - 1]);
+        } else {
+            description.extend(&remaining[..run_length]);
+        }
+        remaining = &remaining[run_length..];
+    }
+    description.push(']');
+    description
 }
 
 impl<const N: usize> From<CharacterGroupClass<N>> for CharacterGroupClassBoxed {
@@ -71,6 +102,10 @@ pub const fn uppercase() -> CharacterGroupClass<26> {
     UPPERCASE
 }
 
+pub const fn blank() -> CharacterGroupClass<2> {
+    BLANK
+}
+
 pub fn alphabetical() -> CharacterGroupClassBoxed {
     lowercase().union(uppercase())
 }
@@ -85,6 +120,10 @@ const DIGITS: CharacterGroupClass<10> = CharacterGroupClass {
     valid_chars: ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'],
 };
 
+const BLANK: CharacterGroupClass<2> = CharacterGroupClass {
+    valid_chars: [' ', '\t'],
+};
+
 const LOWERCASE: CharacterGroupClass<26> = CharacterGroupClass {
     valid_chars: [
         'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r',
@@ -98,3 +137,29 @@ const UPPERCASE: CharacterGroupClass<26> = CharacterGroupClass {
         'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
     ],
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_abbreviates_ranges() {
+        assert_eq!(digits().describe(), "[0-9]");
+        assert_eq!(alphanumeric().describe(), "[a-zA-Z_0-9]");
+        assert_eq!(
+            CharacterGroupClassBoxed::of('a')
+                .union(CharacterGroupClassBoxed::of('b'))
+                .union(CharacterGroupClassBoxed::of('d'))
+                .describe(),
+            "[abd]"
+        );
+    }
+
+    #[test]
+    fn test_group_ignoring_case() {
+        let class = lowercase().ignore_case();
+        assert!(class.matches('Q'));
+        assert!(!class.matches('1'));
+        assert!(!lowercase().ignore_case().negate().matches('Q'));
+    }
+}
@@ -1,8 +1,12 @@
+use crate::character_class::case_folding::case_forms;
 use std::fmt::Debug;
 
 pub trait CharacterClass: Debug {
     fn matches(&self, character: char) -> bool;
 
+    /// A short readable description of the characters that match, used by --debug-pattern
+    fn describe(&self) -> String;
+
     fn union<T: CharacterClass>(self, other: T) -> UnionCharacterClass<Self, T>
     where
         Self: Sized,
@@ -19,12 +23,24 @@ pub trait CharacterClass: Debug {
     {
         NegativeCharacterClass { class: self }
     }
+
+    /// Match any character that has a case form in this class, using Unicode simple case folding
+    fn ignore_case(self) -> CaseInsensitiveCharacterClass<Self>
+    where
+        Self: Sized,
+    {
+        CaseInsensitiveCharacterClass { class: self }
+    }
 }
 
 impl CharacterClass for Box<dyn CharacterClass> {
     fn matches(&self, character: char) -> bool {
         (**self).matches(character)
     }
+
+    fn describe(&self) -> String {
+        (**self).describe()
+    }
 }
 
 #[derive(Debug)]
@@ -37,6 +53,10 @@ impl<T1: CharacterClass, T2: CharacterClass> CharacterClass for UnionCharacterCl
     fn matches(&self, character: char) -> bool {
         self.first.matches(character) || self.second.matches(character)
     }
+
+    fn describe(&self) -> String {
+        format!("{} or {}", self.first.describe(), self.second.describe())
+    }
 }
 
 #[derive(Debug)]
@@ -48,4 +68,23 @@ impl<T: CharacterClass> CharacterClass for NegativeCharacterClass<T> {
     fn matches(&self, character: char) -> bool {
         !self.class.matches(character)
     }
+
+    fn describe(&self) -> String {
+        format!("not ({})", self.class.describe())
+    }
+}
+
+#[derive(Debug)]
+pub struct CaseInsensitiveCharacterClass<T: CharacterClass> {
+    class: T,
+}
+
+impl<T: CharacterClass> CharacterClass for CaseInsensitiveCharacterClass<T> {
+    fn matches(&self, character: char) -> bool {
+        case_forms(character).any(|form| self.class.matches(form))
+    }
+
+    fn describe(&self) -> String {
+        format!("{} ignoring case", self.class.describe())
+    }
 }
@@ -13,6 +13,10 @@ impl CharacterClass for LiteralCharacterClass {
     fn matches(&self, character: char) -> bool {
         character == self.literal
     }
+
+    fn describe(&self) -> String {
+        format!("{:?}", self.literal)
+    }
 }
 
 #[cfg(test)]
@@ -25,4 +29,16 @@ mod tests {
         assert!(class.matches('a'));
         assert!(!class.matches('b'));
     }
+
+    #[test]
+    fn test_literal_ignoring_case() {
+        let class = literal('a').ignore_case();
+        assert!(class.matches('a'));
+        assert!(class.matches('A'));
+        assert!(!class.matches('b'));
+
+        let class = literal('Σ').ignore_case();
+        assert!(class.matches('σ'));
+        assert!(class.matches('ς'));
+    }
 }
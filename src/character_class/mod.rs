@@ -1,9 +1,16 @@
+mod case_folding;
 mod char_group_class;
+#[allow(clippy::module_inception)]
 mod character_class;
 mod literal_character_class;
+mod posix_character_class;
+mod range_character_class;
 mod whitespace_character_class;
 
+pub use case_folding::fold_case;
 pub use char_group_class::*;
 pub use character_class::*;
 pub use literal_character_class::literal;
+pub use posix_character_class::{posix_class, POSIX_CLASS_NAMES};
+pub use range_character_class::range;
 pub use whitespace_character_class::whitespace;
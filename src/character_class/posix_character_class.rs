@@ -0,0 +1,70 @@
+use crate::character_class::{
+    alphabetical, blank, digits, literal, lowercase, range, uppercase, whitespace, CharacterClass,
+};
+
+/// The names of the POSIX character classes, which are written like [:alpha:] inside a character group
+pub const POSIX_CLASS_NAMES: [&str; 12] = [
+    "alnum", "alpha", "blank", "cntrl", "digit", "graph", "lower", "print", "punct", "space",
+    "upper", "xdigit",
+];
+
+/// The POSIX character class with the given name, or None if there is no class with that name
+pub fn posix_class(name: &str) -> Option<Box<dyn CharacterClass>> {
+    let class: Box<dyn CharacterClass> = match name {
+        "alnum" => Box::new(alphabetical().union(digits().into())),
+        "alpha" => Box::new(alphabetical()),
+        "blank" => Box::new(blank()),
+        "cntrl" => Box::new(range('\0', '\x1f').union(literal('\x7f'))),
+        "digit" => Box::new(digits()),
+        "graph" => Box::new(range('!', '~')),
+        "lower" => Box::new(lowercase()),
+        "print" => Box::new(range(' ', '~')),
+        "punct" => Box::new(
+            range('!', '/')
+                .union(range(':', '@'))
+                .union(range('[', '`'))
+                .union(range('{', '~')),
+        ),
+        "space" => Box::new(whitespace()),
+        "upper" => Box::new(uppercase()),
+        "xdigit" => Box::new(digits().union(range('a', 'f')).union(range('A', 'F'))),
+        _ => None?,
+    };
+    Some(class)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matching_ascii_chars(name: &str) -> String {
+        let class = posix_class(name).unwrap();
+        (0..128u8)
+            .map(char::from)
+            .filter(|char| class.matches(*char))
+            .collect()
+    }
+
+    #[test]
+    fn test_every_class_exists() {
+        for name in POSIX_CLASS_NAMES {
+            assert!(posix_class(name).is_some(), "Missing class {name}");
+        }
+        assert!(posix_class("word").is_none());
+    }
+
+    #[test]
+    fn test_class_members() {
+        assert_eq!(matching_ascii_chars("blank"), "\t ");
+        assert_eq!(matching_ascii_chars("xdigit"), "0123456789ABCDEFabcdef");
+        assert_eq!(
+            matching_ascii_chars("punct"),
+            "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~"
+        );
+        assert_eq!(matching_ascii_chars("alnum").len(), 62);
+        assert_eq!(matching_ascii_chars("cntrl").len(), 33);
+        assert_eq!(matching_ascii_chars("graph").len(), 94);
+        assert_eq!(matching_ascii_chars("print").len(), 95);
+        assert_eq!(matching_ascii_chars("space"), "\t\n\x0b\x0c\r ");
+    }
+}
@@ -0,0 +1,55 @@
+use crate::character_class::CharacterClass;
+
+/// Matches any character between start and end (inclusive)
+#[derive(Debug)]
+struct RangeCharacterClass {
+    start: char,
+    end: char,
+}
+
+pub fn range(start: char, end: char) -> impl CharacterClass {
+    RangeCharacterClass { start, end }
+}
+
+impl CharacterClass for RangeCharacterClass {
+    fn matches(&self, character: char) -> bool {
+        (self.start..=self.end).contains(&character)
+    }
+
+    fn describe(&self) -> String {
+        format!("{:?}-{:?}", self.start, self.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_matching() {
+        let class = range('b', 'd');
+        assert!(!class.matches('a'));
+        assert!(class.matches('b'));
+        assert!(class.matches('c'));
+        assert!(class.matches('d'));
+        assert!(!class.matches('e'));
+    }
+
+    #[test]
+    fn test_unicode_range_matching() {
+        let class = range('α', 'ω');
+        assert!(class.matches('λ'));
+        assert!(!class.matches('a'));
+    }
+
+    #[test]
+    fn test_range_ignoring_case() {
+        let class = range('b', 'd').ignore_case();
+        assert!(class.matches('C'));
+        assert!(class.matches('c'));
+        assert!(!class.matches('E'));
+
+        let class = range('J', 'L').ignore_case();
+        assert!(class.matches('\u{212A}')); // Kelvin sign
+    }
+}
@@ -7,6 +7,10 @@ impl CharacterClass for WhitespaceCharacterClass {
     fn matches(&self, character: char) -> bool {
         character.is_whitespace()
     }
+
+    fn describe(&self) -> String {
+        "whitespace".to_string()
+    }
 }
 
 pub fn whitespace() -> impl CharacterClass {
@@ -0,0 +1,402 @@
+use crate::cli::UsageError::{
+    InvalidValue, MissingPattern, MissingValue, UnexpectedValue, UnknownOption,
+};
+use std::path::PathBuf;
+use thiserror::Error;
+
+pub const USAGE: &str = "Usage: grep [OPTION]... PATTERNS [FILE]...";
+
+pub const HELP: &str = "\
+Usage: grep [OPTION]... PATTERNS [FILE]...
+Search for PATTERNS in each FILE, or in standard input when no FILE is given.
+
+Pattern selection:
+  -E, --extended-regexp     PATTERNS are extended regular expressions (the default)
+  -e, --regexp=PATTERNS     use PATTERNS for matching, can be given more than once
+  -i, --ignore-case         ignore case distinctions in patterns and data
+      --no-ignore-case      don't ignore case distinctions (the default)
+  -w, --word-regexp         match only whole words
+
+Input selection:
+  -r, --recursive           search directories recursively
+      --crlf                treat \\r\\n as a line terminator, so the \\r isn't matched as part of the line
+
+Output control:
+  -n, --line-number         prefix each output line with its line number
+  -o, --only-matching       print only the matched parts of each matching line
+      --only-group=NUM      print only the text captured by group NUM in each match
+      --replace=TEMPLATE    replace each match with TEMPLATE, where $NUM and ${NAME} insert a group's text
+
+Miscellaneous:
+      --debug-pattern       print the parsed pattern to stderr
+      --help                display this help text and exit
+  -V, --version             display version information and exit
+
+Exit status is 0 if any line is selected, 1 otherwise, and 2 if an error occurred.";
+
+/// What the command line asked grep to do
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Search(Options),
+    Help,
+    Version,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    /// A line is selected if it matches any of these patterns
+    pub patterns: Vec<String>,
+    /// The files to search, stdin is searched if this is empty
+    pub paths: Vec<PathBuf>,
+    pub recursive: bool,
+    /// Strip \r\n from the end of lines before matching, not just \n
+    pub crlf: bool,
+    /// Match letters in any case, as if each pattern started with (?i)
+    pub ignore_case: bool,
+    /// Only match whole words, as if each pattern was surrounded by \b
+    pub word_regexp: bool,
+    /// Prefix each printed line with the number of the input line it came from
+    pub line_number: bool,
+    /// Print the text captured by this group for each match instead of the whole line, 0 is the whole match
+    pub only_group: Option<usize>,
+    /// Print matches replaced by this template, which can refer to groups like $1 or ${name}
+    pub replacement: Option<String>,
+    /// Print the parsed pattern to stderr before searching
+    pub debug_pattern: bool,
+}
+
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum UsageError {
+    #[error("no pattern was given")]
+    MissingPattern,
+    #[error("unrecognized option '{0}'")]
+    UnknownOption(String),
+    #[error("option '{0}' requires an argument")]
+    MissingValue(String),
+    #[error("option '{0}' doesn't allow an argument")]
+    UnexpectedValue(String),
+    #[error("invalid argument '{1}' for '{0}'")]
+    InvalidValue(String, String),
+}
+
+struct OptionSpec {
+    short: Option<char>,
+    long: &'static str,
+    takes_value: bool,
+}
+
+const OPTIONS: &[OptionSpec] = &[
+    OptionSpec {
+        short: Some('E'),
+        long: "extended-regexp",
+        takes_value: false,
+    },
+    OptionSpec {
+        short: Some('e'),
+        long: "regexp",
+        takes_value: true,
+    },
+    OptionSpec {
+        short: Some('i'),
+        long: "ignore-case",
+        takes_value: false,
+    },
+    OptionSpec {
+        short: None,
+        long: "no-ignore-case",
+        takes_value: false,
+    },
+    OptionSpec {
+        short: Some('w'),
+        long: "word-regexp",
+        takes_value: false,
+    },
+    OptionSpec {
+        short: Some('r'),
+        long: "recursive",
+        takes_value: false,
+    },
+    OptionSpec {
+        short: None,
+        long: "crlf",
+        takes_value: false,
+    },
+    OptionSpec {
+        short: Some('n'),
+        long: "line-number",
+        takes_value: false,
+    },
+    OptionSpec {
+        short: Some('o'),
+        long: "only-matching",
+        takes_value: false,
+    },
+    OptionSpec {
+        short: None,
+        long: "only-group",
+        takes_value: true,
+    },
+    OptionSpec {
+        short: None,
+        long: "replace",
+        takes_value: true,
+    },
+    OptionSpec {
+        short: None,
+        long: "debug-pattern",
+        takes_value: false,
+    },
+    OptionSpec {
+        short: None,
+        long: "help",
+        takes_value: false,
+    },
+    OptionSpec {
+        short: Some('V'),
+        long: "version",
+        takes_value: false,
+    },
+];
+
+/// Parse the command line arguments, not including the program name
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, UsageError> {
+    let mut args = args.into_iter();
+    let mut options = Options::default();
+    let mut positional = Vec::new();
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            positional.extend(args.by_ref());
+        } else if let Some(long_option) = arg.strip_prefix("--") {
+            let (name, value) = match long_option.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long_option, None),
+            };
+            let spec = OPTIONS
+                .iter()
+                .find(|spec| spec.long == name)
+                .ok_or_else(|| UnknownOption(format!("--{name}")))?;
+            let value = match (spec.takes_value, value) {
+                (true, Some(value)) => Some(value),
+                (true, None) => Some(
+                    args.next()
+                        .ok_or_else(|| MissingValue(format!("--{name}")))?,
+                ),
+                (false, Some(_)) => return Err(UnexpectedValue(format!("--{name}"))),
+                (false, None) => None,
+            };
+            if let Some(command) = apply_option(&mut options, spec, value)? {
+                return Ok(command);
+            }
+        } else if let Some(short_options) = arg.strip_prefix('-').filter(|flags| !flags.is_empty())
+        {
+            // short options can be combined, e.g. -rE, and the last one can take a value, e.g. -repattern
+            for (idx, flag) in short_options.char_indices() {
+                let spec = OPTIONS
+                    .iter()
+                    .find(|spec| spec.short == Some(flag))
+                    .ok_or_else(|| UnknownOption(format!("-{flag}")))?;
+                if !spec.takes_value {
+                    if let Some(command) = apply_option(&mut options, spec, None)? {
+                        return Ok(command);
+                    }
+                    continue;
+                }
+                let attached_value = &short_options[idx + flag.len_utf8()..];
+                let value = match attached_value {
+                    "" => args
+                        .next()
+                        .ok_or_else(|| MissingValue(format!("-{flag}")))?,
+                    _ => attached_value.to_string(),
+                };
+                apply_option(&mut options, spec, Some(value))?;
+                break;
+            }
+        } else {
+            positional.push(arg);
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    // the first positional argument is the pattern, unless patterns were given with -e
+    if options.patterns.is_empty() {
+        options
+            .patterns
+            .push(positional.next().ok_or(MissingPattern)?);
+    }
+    options.paths = positional.map(PathBuf::from).collect();
+    Ok(Command::Search(options))
+}
+
+/// Update the options with the given option
+/// Returns the command to run instead of searching, for options like --help
+fn apply_option(
+    options: &mut Options,
+    spec: &OptionSpec,
+    value: Option<String>,
+) -> Result<Option<Command>, UsageError> {
+    match spec.long {
+        "extended-regexp" => {} // patterns are always extended regular expressions
+        "regexp" => options.patterns.extend(value),
+        // the last of --ignore-case and --no-ignore-case wins, so an alias can be overridden
+        "ignore-case" => options.ignore_case = true,
+        "no-ignore-case" => options.ignore_case = false,
+        "word-regexp" => options.word_regexp = true,
+        "recursive" => options.recursive = true,
+        "crlf" => options.crlf = true,
+        "line-number" => options.line_number = true,
+        "only-matching" => options.only_group = Some(0),
+        "only-group" => {
+            let value = value.unwrap_or_default();
+            let group = value
+                .parse()
+                .map_err(|_| InvalidValue(format!("--{}", spec.long), value))?;
+            options.only_group = Some(group);
+        }
+        "replace" => options.replacement = value,
+        "debug-pattern" => options.debug_pattern = true,
+        "help" => return Ok(Some(Command::Help)),
+        "version" => return Ok(Some(Command::Version)),
+        _ => unreachable!("every option in OPTIONS must be handled"),
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, UsageError> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn parse_options(args: &[&str]) -> Options {
+        match parse(args) {
+            Ok(Command::Search(options)) => options,
+            result => panic!("Expected search options for {args:?}, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_pattern_and_paths() {
+        let options = parse_options(&["-E", "a+", "one.txt", "two.txt"]);
+        assert_eq!(options.patterns, vec!["a+"]);
+        assert_eq!(
+            options.paths,
+            vec![PathBuf::from("one.txt"), PathBuf::from("two.txt")]
+        );
+        assert!(!options.recursive);
+    }
+
+    #[test]
+    fn test_combined_short_flags() {
+        let options = parse_options(&["-rE", "a", "dir"]);
+        assert!(options.recursive);
+        assert_eq!(options.patterns, vec!["a"]);
+        assert_eq!(options.paths, vec![PathBuf::from("dir")]);
+    }
+
+    #[test]
+    fn test_repeated_patterns() {
+        let options = parse_options(&["-e", "a", "-rec", "--regexp=d", "--regexp", "e", "f"]);
+        assert_eq!(options.patterns, vec!["a", "c", "d", "e"]);
+        assert_eq!(options.paths, vec![PathBuf::from("f")]);
+        assert!(options.recursive);
+    }
+
+    #[test]
+    fn test_word_regexp() {
+        assert!(!parse_options(&["a"]).word_regexp);
+        assert!(parse_options(&["-w", "a"]).word_regexp);
+        let options = parse_options(&["-wr", "--word-regexp", "a"]);
+        assert!(options.word_regexp);
+        assert!(options.recursive);
+    }
+
+    #[test]
+    fn test_ignore_case() {
+        assert!(!parse_options(&["a"]).ignore_case);
+        assert!(parse_options(&["-i", "a"]).ignore_case);
+        assert!(parse_options(&["--ignore-case", "a"]).ignore_case);
+        assert!(!parse_options(&["-i", "--no-ignore-case", "a"]).ignore_case);
+        assert!(parse_options(&["--no-ignore-case", "-wi", "a"]).ignore_case);
+    }
+
+    #[test]
+    fn test_flag_without_short_form() {
+        let options = parse_options(&["--debug-pattern", "a"]);
+        assert!(options.debug_pattern);
+        assert!(!options.crlf);
+        assert!(parse_options(&["--crlf", "a"]).crlf);
+        assert_eq!(parse(&["-d", "a"]), Err(UnknownOption("-d".to_string())));
+    }
+
+    #[test]
+    fn test_end_of_options() {
+        let options = parse_options(&["--", "-r", "-E"]);
+        assert_eq!(options.patterns, vec!["-r"]);
+        assert_eq!(options.paths, vec![PathBuf::from("-E")]);
+        assert!(!options.recursive);
+    }
+
+    #[test]
+    fn test_help_and_version() {
+        assert_eq!(parse(&["--help", "--unknown"]), Ok(Command::Help));
+        assert_eq!(parse(&["-V"]), Ok(Command::Version));
+        assert_eq!(parse(&["-rV"]), Ok(Command::Version));
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        assert_eq!(parse(&[]), Err(MissingPattern));
+        assert_eq!(parse(&["-r"]), Err(MissingPattern));
+        assert_eq!(parse(&["-x", "a"]), Err(UnknownOption("-x".to_string())));
+        assert_eq!(
+            parse(&["--colour", "a"]),
+            Err(UnknownOption("--colour".to_string()))
+        );
+        assert_eq!(parse(&["-e"]), Err(MissingValue("-e".to_string())));
+        assert_eq!(
+            parse(&["--regexp"]),
+            Err(MissingValue("--regexp".to_string()))
+        );
+        assert_eq!(
+            parse(&["--recursive=yes", "a"]),
+            Err(UnexpectedValue("--recursive".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_line_number() {
+        assert!(!parse_options(&["a"]).line_number);
+        assert!(parse_options(&["--line-number", "a"]).line_number);
+        let options = parse_options(&["-inr", "a", "dir"]);
+        assert!(options.ignore_case);
+        assert!(options.line_number);
+        assert!(options.recursive);
+        assert_eq!(options.paths, vec![PathBuf::from("dir")]);
+    }
+
+    #[test]
+    fn test_only_group() {
+        assert_eq!(parse_options(&["a"]).only_group, None);
+        assert_eq!(parse_options(&["-o", "a"]).only_group, Some(0));
+        assert_eq!(parse_options(&["--only-group=2", "a"]).only_group, Some(2));
+        assert_eq!(
+            parse_options(&["--only-group", "1", "a"]).only_group,
+            Some(1)
+        );
+        assert_eq!(
+            parse(&["--only-group=x", "a"]),
+            Err(InvalidValue("--only-group".to_string(), "x".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_replace() {
+        assert_eq!(parse_options(&["a"]).replacement, None);
+        let options = parse_options(&["--replace=$1", "(a)"]);
+        assert_eq!(options.replacement, Some("$1".to_string()));
+        assert_eq!(options.patterns, vec!["(a)"]);
+    }
+}
@@ -1,33 +1,81 @@
 mod character_class;
+mod cli;
 mod matching;
 mod parse;
 mod pattern;
+mod search;
 
-use matching::match_pattern;
+use cli::{parse_args, Command, HELP, USAGE};
+use matching::{compile_patterns, CompileOptions};
+use pattern::Pattern;
+use search::{search_paths, search_stdin, LineMatcher};
 use std::env;
 use std::io;
+use std::path::PathBuf;
 use std::process;
 
-// Usage: echo <input_text> | run.sh -E <pattern>
+// Usage: cat <input_file> | run.sh -E <pattern>
+//        run.sh [OPTION]... <pattern> <path>...
 fn main() {
-    // You can use print statements as follows for debugging, they'll be visible when running tests.
-    eprintln!("Logs from your program will appear here!");
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(Command::Search(options)) => options,
+        Ok(Command::Help) => {
+            println!("{HELP}");
+            process::exit(0)
+        }
+        Ok(Command::Version) => {
+            println!("grep {}", env!("CARGO_PKG_VERSION"));
+            process::exit(0)
+        }
+        Err(error) => {
+            eprintln!("grep: {error}");
+            eprintln!("{USAGE}");
+            eprintln!("Try 'grep --help' for more information.");
+            process::exit(2)
+        }
+    };
 
-    if env::args().nth(1).unwrap() != "-E" {
-        println!("Expected first argument to be '-E'");
-        process::exit(1);
+    let compile_options = CompileOptions {
+        word_regexp: options.word_regexp,
+        ignore_case: options.ignore_case,
+    };
+    let pattern = match compile_patterns(&options.patterns, compile_options) {
+        Ok(pattern) => pattern,
+        Err(error) => {
+            eprintln!("grep: invalid pattern: {error}");
+            process::exit(2)
+        }
+    };
+    if options.debug_pattern {
+        eprint!("{}", pattern.tree());
     }
+    if let Some(group) = options.only_group {
+        if group > pattern.group_count() {
+            eprintln!(
+                "grep: invalid group {group}, the pattern has {} groups",
+                pattern.group_count()
+            );
+            process::exit(2)
+        }
+    }
+    let matcher = LineMatcher {
+        pattern: &pattern,
+        only_group: options.only_group,
+        replacement: options.replacement.as_deref(),
+        crlf: options.crlf,
+        line_number: options.line_number,
+    };
+    let mut paths = options.paths;
+    // a recursive search without any paths searches the working directory
+    if options.recursive && paths.is_empty() {
+        paths.push(PathBuf::from("."));
+    }
+    let mut stdout = io::stdout().lock();
 
-    let pattern = env::args().nth(2).unwrap();
-
-    let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
-
-    if match_pattern(&input_line, &pattern) {
-        println!("Matched");
-        process::exit(0)
+    let outcome = if paths.is_empty() {
+        search_stdin(&matcher, &mut stdout)
     } else {
-        println!("No Match");
-        process::exit(1)
-    }
+        search_paths(&paths, options.recursive, &matcher, &mut stdout)
+    };
+    process::exit(outcome.exit_code())
 }
@@ -1,25 +1,154 @@
+use crate::character_class::POSIX_CLASS_NAMES;
 use crate::matching::ParsePatternError::{
-    InvalidEndLineAnchor, InvalidStartLineAnchor, UnmatchedBracket,
+    DuplicateGroupName, EmptyCharacterGroup, InvalidBackreference, InvalidCharacterRange,
+    InvalidCodePoint, InvalidGroupName, InvalidRepetitionBounds, NothingToRepeat,
+    TrailingBackslash, UnknownCharacterClass, UnknownEscape, UnmatchedBracket,
+    UnmatchedClosingBracket,
+};
+use crate::parse::{
+    is_character_escape, is_valid_group_name, parse_character_escape, parse_repetition_bounds,
+    split_all_at_top_level, split_at, split_at_character_group_end, split_at_top_level,
 };
-use crate::parse::split_at;
 use crate::pattern::{
-    alphanumeric, alternating, always_match, digits, end_line_anchor, literal, one_or_more,
-    start_line_anchor, union, whitespace, wildcard, zero_or_one, ChainablePattern, Pattern,
+    alphanumeric, alternating, always_match, any_of, atomic, backreference, capture_group,
+    case_insensitive_backreference, case_insensitive_literal, digits, end_line_anchor, lazy_repeat,
+    literal, lookahead, lookbehind, negative_lookahead, negative_lookbehind, non_alphanumeric,
+    non_digits, non_whitespace, not_word_boundary, one_or_more, repeat, start_line_anchor,
+    string_end, string_end_before_newline, string_start, union, whitespace, wildcard,
+    word_boundary, word_end, word_start, zero_or_more, zero_or_one, Captures, ChainablePattern,
+    Match, MatchIndices, MatchState, Pattern, PatternTree,
 };
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq, Clone)]
-enum ParsePatternError {
-    #[error(
-        "Start line anchor must be at the start of the pattern, instead found it at col number {0}"
-    )]
-    InvalidStartLineAnchor(usize),
-    #[error(
-        "End line anchor must be at the end of the pattern, instead found it at col number {0}"
-    )]
-    InvalidEndLineAnchor(usize),
+pub enum ParsePatternError {
     #[error("Unmatched opening bracket at col number {0}")]
     UnmatchedBracket(usize),
+    #[error("Character group at col number {0} must list at least one character")]
+    EmptyCharacterGroup(usize),
+    #[error("Quantifier at col number {0} doesn't follow anything it can repeat")]
+    NothingToRepeat(usize),
+    #[error("Unmatched closing bracket at col number {0}")]
+    UnmatchedClosingBracket(usize),
+    #[error("Repetition at col number {0} has a count that is too large, or a minimum count larger than its maximum count")]
+    InvalidRepetitionBounds(usize),
+    #[error("Character range at col number {0} ends before it starts")]
+    InvalidCharacterRange(usize),
+    #[error("Unknown character class at col number {0}")]
+    UnknownCharacterClass(usize),
+    #[error("Unknown escape sequence at col number {0}")]
+    UnknownEscape(usize),
+    #[error("Pattern can't end with an unescaped backslash, found one at col number {0}")]
+    TrailingBackslash(usize),
+    #[error("Invalid code point escape at col number {0}")]
+    InvalidCodePoint(usize),
+    #[error("Backreference at col number {0} refers to a group that doesn't start before it")]
+    InvalidBackreference(usize),
+    #[error("Invalid group name at col number {0}")]
+    InvalidGroupName(usize),
+    #[error("Group name at col number {0} is already used by another group")]
+    DuplicateGroupName(usize),
+}
+
+impl ParsePatternError {
+    /// The col number of the character that caused the error
+    pub fn col_number(&self) -> usize {
+        match self {
+            UnmatchedBracket(col_number)
+            | EmptyCharacterGroup(col_number)
+            | NothingToRepeat(col_number)
+            | UnmatchedClosingBracket(col_number)
+            | InvalidRepetitionBounds(col_number)
+            | InvalidCharacterRange(col_number)
+            | UnknownCharacterClass(col_number)
+            | UnknownEscape(col_number)
+            | TrailingBackslash(col_number)
+            | InvalidCodePoint(col_number)
+            | InvalidBackreference(col_number)
+            | InvalidGroupName(col_number)
+            | DuplicateGroupName(col_number) => *col_number,
+        }
+    }
+
+    /// Suggest how the pattern can be fixed, usually by escaping the character that caused the error
+    fn hint(&self, pattern: &str) -> Option<String> {
+        match self {
+            EmptyCharacterGroup(_) => Some("did you mean `\\[\\]`?".to_string()),
+            UnknownEscape(col_number) => pattern.chars().nth(col_number + 1).map(|char| {
+                format!("did you mean `\\\\{char}` to match a backslash and `{char}`?")
+            }),
+            InvalidCodePoint(_) => Some(
+                "code points are written as `\\xHH`, `\\x{HHHH}` or `\\u{HHHH}`, up to 10FFFF"
+                    .to_string(),
+            ),
+            InvalidBackreference(_) => Some(
+                "groups are numbered from 1 in the order of their opening brackets".to_string(),
+            ),
+            InvalidGroupName(_) => Some(
+                "group names start with a letter or _, followed by letters, digits or _, e.g. `(?<year>\\d+)`"
+                    .to_string(),
+            ),
+            DuplicateGroupName(_) => Some("each group needs a different name".to_string()),
+            UnknownCharacterClass(_) => Some(format!(
+                "the known classes are {}",
+                POSIX_CLASS_NAMES
+                    .map(|name| format!("[:{name}:]"))
+                    .join(", ")
+            )),
+            InvalidRepetitionBounds(col_number) => {
+                let bounds: Vec<char> = pattern.chars().skip(col_number + 1).collect();
+                match parse_repetition_bounds(&bounds)? {
+                    Ok((min, Some(max), _)) => Some(format!("did you mean `{{{max},{min}}}`?")),
+                    _ => Some(format!("counts can be at most {}", usize::MAX)),
+                }
+            }
+            InvalidCharacterRange(col_number) => {
+                let range: Vec<char> = pattern.chars().skip(*col_number).take(3).collect();
+                match range[..] {
+                    [start, '-', end] => Some(format!("did you mean `{end}-{start}`?")),
+                    _ => None,
+                }
+            }
+            _ => pattern
+                .chars()
+                .nth(self.col_number())
+                .map(|char| format!("did you mean `\\{char}`?")),
+        }
+    }
+
+    /// Render the error like rustc does, echoing the pattern with a caret under the col that caused the error
+    pub fn render(&self, pattern: &str) -> String {
+        let caret_indent = " ".repeat(self.col_number());
+        let mut rendered = format!("{self}\n  |\n  | {pattern}\n  | {caret_indent}^");
+        if let Some(hint) = self.hint(pattern) {
+            rendered.push_str(&format!("\n  = help: {hint}"));
+        }
+        rendered
+    }
+}
+
+/// A ParsePatternError along with the pattern that caused it, displayed with ParsePatternError::render
+#[derive(Error, Debug, PartialEq, Clone)]
+#[error("{}", .error.render(.pattern))]
+pub struct InvalidPatternError {
+    pub pattern: String,
+    pub error: ParsePatternError,
+}
+
+/// What the parser has found so far that affects the rest of the pattern
+struct ParseContext {
+    /// The name of each group that has been numbered so far, or None if the group isn't named
+    group_names: Vec<Option<String>>,
+    /// Whether letters match in any case, set by `(?i)` until the end of the enclosing group
+    ignore_case: bool,
+}
+
+/// Match the character, in any case if `ignore_case` is set
+fn literal_pattern(char: char, ignore_case: bool) -> Box<dyn ChainablePattern> {
+    match ignore_case {
+        true => Box::new(case_insensitive_literal(char)),
+        false => Box::new(literal(char)),
+    }
 }
 
 /// Add modifiers such as + to the current pattern, then parse the remaining pattern using construct_pattern
@@ -27,318 +156,1345 @@ fn parse_modifiers(
     pattern: Box<dyn ChainablePattern>,
     pattern_chars: &[char],
     char_idx: usize,
+    context: &mut ParseContext,
 ) -> Result<Box<dyn ChainablePattern>, ParsePatternError> {
     match pattern_chars {
-        ['+', remaining @ ..] => {
-            Ok(one_or_more(pattern).followed_by(construct_pattern(remaining, char_idx + 1)?))
-        }
-        ['?', remaining @ ..] => {
-            Ok(zero_or_one(pattern).followed_by(construct_pattern(remaining, char_idx + 1)?))
-        }
-        _ => Ok(pattern.followed_by(construct_pattern(pattern_chars, char_idx)?)),
+        // a ? after a modifier makes it lazy, so that it repeats as few times as possible
+        ['+', '?', remaining @ ..] => Ok(lazy_repeat(pattern, 1, None)
+            .followed_by(construct_pattern(remaining, char_idx + 2, context)?)),
+        ['?', '?', remaining @ ..] => Ok(lazy_repeat(pattern, 0, Some(1))
+            .followed_by(construct_pattern(remaining, char_idx + 2, context)?)),
+        ['*', '?', remaining @ ..] => Ok(lazy_repeat(pattern, 0, None)
+            .followed_by(construct_pattern(remaining, char_idx + 2, context)?)),
+        // a + after a modifier makes it possessive, so that it never gives up what it matched
+        ['+', '+', remaining @ ..] => Ok(atomic(Box::new(one_or_more(pattern)))
+            .followed_by(construct_pattern(remaining, char_idx + 2, context)?)),
+        ['?', '+', remaining @ ..] => Ok(atomic(Box::new(zero_or_one(pattern)))
+            .followed_by(construct_pattern(remaining, char_idx + 2, context)?)),
+        ['*', '+', remaining @ ..] => Ok(atomic(Box::new(zero_or_more(pattern)))
+            .followed_by(construct_pattern(remaining, char_idx + 2, context)?)),
+        ['+', remaining @ ..] => Ok(one_or_more(pattern).followed_by(construct_pattern(
+            remaining,
+            char_idx + 1,
+            context,
+        )?)),
+        ['?', remaining @ ..] => Ok(zero_or_one(pattern).followed_by(construct_pattern(
+            remaining,
+            char_idx + 1,
+            context,
+        )?)),
+        ['*', remaining @ ..] => Ok(zero_or_more(pattern).followed_by(construct_pattern(
+            remaining,
+            char_idx + 1,
+            context,
+        )?)),
+        // a { that doesn't start valid bounds is a literal, and will be parsed by construct_pattern
+        ['{', bounds @ ..] => match parse_repetition_bounds(bounds) {
+            None => Ok(pattern.followed_by(construct_pattern(pattern_chars, char_idx, context)?)),
+            Some(Err(_)) => Err(InvalidRepetitionBounds(char_idx)),
+            Some(Ok((min, Some(max), _))) if min > max => Err(InvalidRepetitionBounds(char_idx)),
+            Some(Ok((min, max, ['?', remaining @ ..]))) => Ok(lazy_repeat(pattern, min, max)
+                .followed_by(construct_pattern(
+                    remaining,
+                    char_idx + pattern_chars.len() - remaining.len(),
+                    context,
+                )?)),
+            Some(Ok((min, max, ['+', remaining @ ..]))) => Ok(atomic(Box::new(repeat(
+                pattern, min, max,
+            )))
+            .followed_by(construct_pattern(
+                remaining,
+                char_idx + pattern_chars.len() - remaining.len(),
+                context,
+            )?)),
+            Some(Ok((min, max, remaining))) => {
+                Ok(repeat(pattern, min, max).followed_by(construct_pattern(
+                    remaining,
+                    char_idx + pattern_chars.len() - remaining.len(),
+                    context,
+                )?))
+            }
+        },
+        _ => Ok(pattern.followed_by(construct_pattern(pattern_chars, char_idx, context)?)),
     }
 }
 
 fn construct_pattern(
     pattern_chars: &[char],
     char_idx: usize,
+    context: &mut ParseContext,
 ) -> Result<Box<dyn ChainablePattern>, ParsePatternError> {
     match pattern_chars {
-        ['^', ..] => Err(InvalidStartLineAnchor(char_idx)),
-        ['$', ..] => Err(InvalidEndLineAnchor(char_idx)),
-        ['.', remaining @ ..] => parse_modifiers(Box::new(wildcard()), remaining, char_idx + 1),
+        // anchors can't be repeated, so a modifier after one is parsed as the start of the next pattern
+        ['^', remaining @ ..] => Ok(start_line_anchor().followed_by(construct_pattern(
+            remaining,
+            char_idx + 1,
+            context,
+        )?)),
+        ['$', remaining @ ..] => {
+            Ok(end_line_anchor().followed_by(construct_pattern(remaining, char_idx + 1, context)?))
+        }
+        // modifiers are consumed by parse_modifiers, so reaching one here means there is nothing before it
+        ['+' | '?' | '*', ..] => Err(NothingToRepeat(char_idx)),
+        [')', ..] => Err(UnmatchedClosingBracket(char_idx)),
+        ['{', bounds @ ..] if parse_repetition_bounds(bounds).is_some() => {
+            Err(NothingToRepeat(char_idx))
+        }
+        ['.', remaining @ ..] => {
+            parse_modifiers(Box::new(wildcard()), remaining, char_idx + 1, context)
+        }
+        ['\\', char, ..] if is_character_escape(*char) => {
+            let (char, remaining) = parse_character_escape(&pattern_chars[1..], char_idx)?;
+            let remaining_idx = char_idx + pattern_chars.len() - remaining.len();
+            parse_modifiers(
+                literal_pattern(char, context.ignore_case),
+                remaining,
+                remaining_idx,
+                context,
+            )
+        }
         ['\\', char, remaining @ ..] => parse_modifiers(
             match char {
                 'd' => Box::new(digits()),
                 'w' => Box::new(alphanumeric()),
                 's' => Box::new(whitespace()),
-                // if escape isn't followed by an escaped character, assume it is a literal escape
-                _ => literal('\\').followed_by(Box::new(literal(*char))),
+                'D' => Box::new(non_digits()),
+                'W' => Box::new(non_alphanumeric()),
+                'S' => Box::new(non_whitespace()),
+                'b' => Box::new(word_boundary()),
+                'B' => Box::new(not_word_boundary()),
+                '<' => Box::new(word_start()),
+                '>' => Box::new(word_end()),
+                'A' => Box::new(string_start()),
+                'z' => Box::new(string_end()),
+                'Z' => Box::new(string_end_before_newline()),
+                // a backreference can only refer to a group that has already started
+                '1'..='9' => match char.to_digit(10).map(|group| group as usize) {
+                    Some(group) if group <= context.group_names.len() => {
+                        match context.ignore_case {
+                            true => Box::new(case_insensitive_backreference(group)),
+                            false => Box::new(backreference(group)),
+                        }
+                    }
+                    _ => return Err(InvalidBackreference(char_idx)),
+                },
+                // escaping a character that isn't a letter or digit, such as \. or \(, matches it literally
+                // letters and digits are reserved for escapes with a special meaning
+                _ if !char.is_alphanumeric() => literal_pattern(*char, context.ignore_case),
+                _ => return Err(UnknownEscape(char_idx)),
             },
             remaining,
             char_idx + 2,
+            context,
         ),
-        ['(', remaining @ ..] => match split_at(remaining, ')') {
+        ['\\'] => Err(TrailingBackslash(char_idx)),
+        // flags apply to the rest of the enclosing group, so they are parsed as the start of the next pattern
+        ['(', '?', 'i', ')', remaining @ ..] => {
+            context.ignore_case = true;
+            construct_pattern(remaining, char_idx + 4, context)
+        }
+        ['(', '?', '-', 'i', ')', remaining @ ..] => {
+            context.ignore_case = false;
+            construct_pattern(remaining, char_idx + 5, context)
+        }
+        // groups can be nested, so find the closing bracket at the same depth as this opening bracket
+        ['(', remaining @ ..] => match split_at_top_level(remaining, ')') {
             None => Err(UnmatchedBracket(char_idx)),
-            Some((chars, remaining)) => match split_at(chars, '|') {
-                None => parse_modifiers(
-                    construct_pattern(chars, char_idx + 1)?,
-                    remaining,
-                    char_idx + chars.len() + 2,
-                ),
-                Some((first_pattern, second_pattern)) => parse_modifiers(
-                    Box::new(alternating(
-                        construct_pattern(first_pattern, char_idx + 1)?,
-                        construct_pattern(second_pattern, char_idx + 2 + first_pattern.len())?,
-                    )),
-                    remaining,
-                    char_idx + chars.len() + 3,
-                ),
-            },
+            Some((chars, remaining)) => parse_modifiers(
+                construct_bracketed_group(chars, char_idx, context)?,
+                remaining,
+                char_idx + chars.len() + 2,
+                context,
+            ),
         },
-        // we match starting from the '[' part, and then manually check for the first closing ']'
-        ['[', remaining @ ..] => match split_at(remaining, ']') {
+        // we match starting from the '[' part, and then manually check for the closing ']'
+        ['[', remaining @ ..] => match split_at_character_group_end(remaining) {
+            // a ']' listed first is a member, so without another ']' it looks like an empty group
+            None if matches!(remaining, [']', ..] | ['^', ']', ..]) => {
+                Err(EmptyCharacterGroup(char_idx))
+            }
             None => Err(UnmatchedBracket(char_idx)),
             Some((chars, remaining)) => parse_modifiers(
-                Box::new(union(chars)),
+                Box::new(union(chars, char_idx, context.ignore_case)?),
                 remaining,
                 char_idx + chars.len() + 2,
+                context,
             ),
         },
-        [char, remaining @ ..] => {
-            parse_modifiers(Box::new(literal(*char)), remaining, char_idx + 1)
-        }
+        [char, remaining @ ..] => parse_modifiers(
+            literal_pattern(*char, context.ignore_case),
+            remaining,
+            char_idx + 1,
+            context,
+        ),
         [] => Ok(Box::new(always_match())), // an empty pattern matches anything
     }
 }
 
-// Handle start and end line anchors in the pattern
-// This is a separate function because start and end line anchors don't implement ChainablePattern
-// and construct_pattern needs to return ChainablePattern for its recursion to work
-fn construct_pattern_with_anchors(
+/// Construct a group from the chars inside its brackets, where `char_idx` is the col number of its opening bracket
+/// Groups capture what they match unless they start with ?:, ?> or a lookaround such as ?=,
+/// and can be named with ?<name> or ?P<name>
+/// Groups starting with ?i: or ?-i: don't capture, and turn ignoring case on or off inside them
+fn construct_bracketed_group(
+    chars: &[char],
+    char_idx: usize,
+    context: &mut ParseContext,
+) -> Result<Box<dyn ChainablePattern>, ParsePatternError> {
+    let (name, inner_chars) = match chars {
+        ['?', ':', inner_chars @ ..] => {
+            return construct_group(inner_chars, char_idx + 3, context);
+        }
+        ['?', 'i', ':', inner_chars @ ..] => {
+            return construct_group_with_case(inner_chars, char_idx + 4, context, true);
+        }
+        ['?', '-', 'i', ':', inner_chars @ ..] => {
+            return construct_group_with_case(inner_chars, char_idx + 5, context, false);
+        }
+        ['?', '>', inner_chars @ ..] => {
+            let inner_pattern = construct_group(inner_chars, char_idx + 3, context)?;
+            return Ok(Box::new(atomic(inner_pattern)));
+        }
+        ['?', '=', inner_chars @ ..] => {
+            let inner_pattern = construct_group(inner_chars, char_idx + 3, context)?;
+            return Ok(Box::new(lookahead(inner_pattern)));
+        }
+        ['?', '!', inner_chars @ ..] => {
+            let inner_pattern = construct_group(inner_chars, char_idx + 3, context)?;
+            return Ok(Box::new(negative_lookahead(inner_pattern)));
+        }
+        ['?', '<', '=', inner_chars @ ..] => {
+            let inner_pattern = construct_group(inner_chars, char_idx + 4, context)?;
+            return Ok(Box::new(lookbehind(inner_pattern)));
+        }
+        ['?', '<', '!', inner_chars @ ..] => {
+            let inner_pattern = construct_group(inner_chars, char_idx + 4, context)?;
+            return Ok(Box::new(negative_lookbehind(inner_pattern)));
+        }
+        ['?', '<', name_and_inner @ ..] | ['?', 'P', '<', name_and_inner @ ..] => {
+            let name_idx = char_idx + 1 + chars.len() - name_and_inner.len();
+            let (name, inner_chars) = split_at(name_and_inner, '>')
+                .filter(|(name, _)| is_valid_group_name(name))
+                .ok_or(InvalidGroupName(name_idx))?;
+            let name: String = name.iter().collect();
+            if context.group_names.contains(&Some(name.clone())) {
+                return Err(DuplicateGroupName(name_idx));
+            }
+            (Some(name), inner_chars)
+        }
+        _ => (None, chars),
+    };
+    // groups are numbered in the order of their opening brackets, so number this group before
+    // any groups nested inside it
+    context.group_names.push(name);
+    let group = context.group_names.len();
+    let inner_idx = char_idx + 1 + chars.len() - inner_chars.len();
+    Ok(Box::new(capture_group(
+        group,
+        construct_group(inner_chars, inner_idx, context)?,
+    )))
+}
+
+/// Construct the pattern inside a group's brackets, which may contain alternatives separated by |
+/// A flag like `(?i)` applies to the rest of the group, including the alternatives after it
+fn construct_group(
     pattern_chars: &[char],
     char_idx: usize,
-) -> Result<Box<dyn Pattern>, ParsePatternError> {
-    match pattern_chars {
-        ['^', remaining @ ..] => Ok(Box::new(start_line_anchor(construct_pattern_with_anchors(
-            remaining,
-            char_idx + 1,
-        )?))),
-        [remaining @ .., '$'] => Ok(Box::new(end_line_anchor(construct_pattern(
-            remaining, char_idx,
-        )?))),
-        ['$', ..] => Err(InvalidEndLineAnchor(char_idx)),
-        _ => construct_pattern(pattern_chars, char_idx).map(|p| p as Box<dyn Pattern>),
+    context: &mut ParseContext,
+) -> Result<Box<dyn ChainablePattern>, ParsePatternError> {
+    let ignore_case = context.ignore_case;
+    let options = split_alternatives(pattern_chars, char_idx)
+        .map(|(option, option_idx)| construct_pattern(option, option_idx, context))
+        .collect::<Result<Vec<_>, _>>();
+    context.ignore_case = ignore_case;
+    let mut options = options?;
+    match options.len() {
+        1 => Ok(options.remove(0)),
+        _ => Ok(Box::new(alternating(options))),
     }
 }
 
-fn construct_pattern_from_str(pattern: &str) -> Result<Box<dyn Pattern>, ParsePatternError> {
-    let pattern_chars: Box<[char]> = pattern.chars().collect();
-    construct_pattern_with_anchors(&pattern_chars, 0)
+/// Construct a group like construct_group, ignoring case inside it if `ignore_case` is set
+fn construct_group_with_case(
+    pattern_chars: &[char],
+    char_idx: usize,
+    context: &mut ParseContext,
+    ignore_case: bool,
+) -> Result<Box<dyn ChainablePattern>, ParsePatternError> {
+    let outer_ignore_case = context.ignore_case;
+    context.ignore_case = ignore_case;
+    let pattern = construct_group(pattern_chars, char_idx, context);
+    context.ignore_case = outer_ignore_case;
+    pattern
+}
+
+/// Split the pattern at each top level |, returning each alternative along with its col number
+fn split_alternatives(
+    pattern_chars: &[char],
+    char_idx: usize,
+) -> impl Iterator<Item = (&[char], usize)> {
+    split_all_at_top_level(pattern_chars, '|')
+        .into_iter()
+        .scan(char_idx, |option_idx, option| {
+            let current_idx = *option_idx;
+            *option_idx += option.len() + 1; // skip past the option and its |
+            Some((option, current_idx))
+        })
+}
+
+/// Settings that apply to a whole pattern, rather than being written in it
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CompileOptions {
+    /// Only match whole words, as if the whole pattern was surrounded by `\b`
+    pub word_regexp: bool,
+    /// Match letters in any case, as if the whole pattern started with `(?i)`
+    pub ignore_case: bool,
+}
+
+/// A parsed pattern, along with the names of the capture groups it contains
+#[derive(Debug)]
+pub struct CompiledPattern {
+    pattern: Box<dyn Pattern>,
+    /// The name of each group starting from group 1, or None if the group isn't named
+    group_names: Vec<Option<String>>,
 }
 
-pub fn match_pattern(input_line: &str, pattern: &str) -> bool {
-    match construct_pattern_from_str(pattern) {
-        Err(error) => panic!("Invalid pattern: {error:?}"),
-        Ok(pattern) => {
-            let pattern_str = format!("{pattern:?}");
-            println!("Parsed pattern: {pattern_str}");
-            pattern.matches(input_line)
+impl CompiledPattern {
+    /// The number of capture groups, not including group 0 which is the whole match
+    pub fn group_count(&self) -> usize {
+        self.group_names.len()
+    }
+
+    /// Find the first match in the line, along with the groups it captured
+    #[allow(dead_code)] // part of the library API, the CLI uses captures_iter
+    pub fn captures(&self, line: &str) -> Option<Captures> {
+        let chars: Box<[char]> = line.chars().collect();
+        self.pattern
+            .find_from(&chars, 0)
+            .map(|(indices, state)| Captures::new(&indices, &state, &self.group_names))
+    }
+
+    /// Find every match in the line that doesn't overlap an earlier match, along with the groups each captured
+    pub fn captures_iter(&self, line: &str) -> Vec<Captures> {
+        let chars: Box<[char]> = line.chars().collect();
+        let mut all_captures = Vec::new();
+        let mut start = 0;
+        while let Some((indices, state)) = self.pattern.find_from(&chars, start) {
+            all_captures.push(Captures::new(&indices, &state, &self.group_names));
+            // an empty match would be found again at the same index, so move past it
+            start = match indices.end > indices.start {
+                true => indices.end,
+                false => indices.end + 1,
+            };
+            if start > chars.len() {
+                break;
+            }
         }
+        all_captures
     }
 }
 
+impl Pattern for CompiledPattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        self.pattern.matches_exact(chars, state)
+    }
+
+    fn tree(&self) -> PatternTree {
+        self.pattern.tree()
+    }
+
+    fn find_from<'a>(
+        &self,
+        chars: &'a [char],
+        start: usize,
+    ) -> Option<(MatchIndices, MatchState<'a>)> {
+        self.pattern.find_from(chars, start)
+    }
+}
+
+/// Parse the pattern once, so it can be matched against many input lines
+/// Alternatives at the top level of the pattern are tried in order, so earlier alternatives have priority
+/// A backslash before any character that isn't a letter or digit matches that character literally, e.g. `\.`
+/// A backslash before a letter or digit without a special meaning, such as `\q`, is an UnknownEscape error
+/// Each `(...)` is a capture group, numbered from 1 in the order of the opening brackets
+/// Groups can also be named with `(?<name>...)` or `(?P<name>...)`, or not capture anything with `(?:...)`
+/// Backreferences `\1` to `\9` match the text most recently captured by a group that starts before them
+/// Word boundaries `\b`, `\B`, `\<` and `\>` treat the characters matched by `\w` as word characters
+/// Anchors `^`, `$` and `\A`, `\z`, `\Z` can be used anywhere, e.g. `(^foo|bar$)`, and match an empty string
+/// Modifiers repeat as many times as possible, unless followed by `?` to make them lazy, e.g. `<.+?>`
+/// Modifiers followed by `+` and atomic groups `(?>...)` never give up what they matched to let the rest match
+/// Lookahead `(?=...)`, `(?!...)` and lookbehind `(?<=...)`, `(?<!...)` check the text around them without matching it
+/// `(?i)` ignores case for the rest of the group it's in and `(?-i)` stops ignoring it, or `(?i:...)` only inside a group
+pub fn compile_pattern(pattern: &str) -> Result<CompiledPattern, ParsePatternError> {
+    compile_pattern_with_options(pattern, CompileOptions::default())
+}
+
+/// Parse the pattern like compile_pattern, with the settings in `options`
+pub fn compile_pattern_with_options(
+    pattern: &str,
+    options: CompileOptions,
+) -> Result<CompiledPattern, ParsePatternError> {
+    let pattern_chars: Box<[char]> = pattern.chars().collect();
+    let mut context = ParseContext {
+        group_names: Vec::new(),
+        ignore_case: options.ignore_case,
+    };
+    // the whole pattern is parsed like the inside of a group, so top level alternatives are tried in order
+    let mut pattern = construct_group(&pattern_chars, 0, &mut context)?;
+    if options.word_regexp {
+        pattern = word_boundary()
+            .followed_by(pattern)
+            .followed_by(Box::new(word_boundary()));
+    }
+    Ok(CompiledPattern {
+        pattern,
+        group_names: context.group_names,
+    })
+}
+
+/// Compile each pattern, producing a Pattern that matches when any of them match
+/// Each pattern numbers its capture groups from 1, so a group number can refer to a group in any of them
+/// A group is named by the first pattern that names a group with its number
+pub fn compile_patterns(
+    patterns: &[String],
+    options: CompileOptions,
+) -> Result<CompiledPattern, InvalidPatternError> {
+    let mut compiled_patterns = patterns
+        .iter()
+        .map(|pattern| {
+            compile_pattern_with_options(pattern, options).map_err(|error| InvalidPatternError {
+                pattern: pattern.clone(),
+                error,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if compiled_patterns.len() == 1 {
+        return Ok(compiled_patterns.remove(0));
+    }
+    let group_count = compiled_patterns
+        .iter()
+        .map(CompiledPattern::group_count)
+        .max()
+        .unwrap_or_default();
+    let group_names = (0..group_count)
+        .map(|group| {
+            compiled_patterns
+                .iter()
+                .find_map(|pattern| pattern.group_names.get(group).cloned().flatten())
+        })
+        .collect();
+    Ok(CompiledPattern {
+        pattern: Box::new(any_of(
+            compiled_patterns
+                .into_iter()
+                .map(|pattern| Box::new(pattern) as Box<dyn Pattern>)
+                .collect(),
+        )),
+        group_names,
+    })
+}
+
+#[allow(dead_code)] // this is useful for tests
+pub fn match_pattern(input_line: &str, pattern: &str) -> Result<bool, ParsePatternError> {
+    Ok(compile_pattern(pattern)?.matches(input_line))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn match_single_char() {
-        assert!(match_pattern("a", "a"));
-        assert!(match_pattern("hello", "e"));
-        assert!(!match_pattern("hello", "a"));
+    fn match_single_char() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("a", "a")?);
+        assert!(match_pattern("hello", "e")?);
+        assert!(!match_pattern("hello", "a")?);
+        Ok(())
     }
 
     #[test]
-    fn match_empty() {
-        assert!(match_pattern("", ""));
+    fn match_empty() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("", "")?);
+        Ok(())
     }
 
     #[test]
-    fn match_digit() {
-        assert!(match_pattern("1", "\\d"));
-        assert!(match_pattern("hell1o", "\\d"));
-        assert!(match_pattern("1235a", "\\d"));
-        assert!(!match_pattern("hello", "\\d"));
+    fn match_digit() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("1", "\\d")?);
+        assert!(match_pattern("hell1o", "\\d")?);
+        assert!(match_pattern("1235a", "\\d")?);
+        assert!(!match_pattern("hello", "\\d")?);
+        Ok(())
     }
 
     #[test]
-    fn match_alpha() {
-        assert!(match_pattern("a", "\\w"));
-        assert!(match_pattern("hell1o", "\\w"));
-        assert!(match_pattern("1235a", "\\w"));
-        assert!(match_pattern("hello", "\\w"));
-        assert!(match_pattern("1", "\\w"));
-        assert!(match_pattern("_", "\\w"));
-        assert!(!match_pattern("[]/.,", "\\w"));
+    fn match_alpha() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("a", "\\w")?);
+        assert!(match_pattern("hell1o", "\\w")?);
+        assert!(match_pattern("1235a", "\\w")?);
+        assert!(match_pattern("hello", "\\w")?);
+        assert!(match_pattern("1", "\\w")?);
+        assert!(match_pattern("_", "\\w")?);
+        assert!(!match_pattern("[]/.,", "\\w")?);
+        Ok(())
     }
 
     #[test]
-    fn match_whitespace() {
-        assert!(match_pattern(" ", "\\s"));
-        assert!(match_pattern("abcd e", "\\s"));
-        assert!(match_pattern("\t", "\\s"));
-        assert!(match_pattern("\n", "\\s"));
+    fn match_whitespace() -> Result<(), ParsePatternError> {
+        assert!(match_pattern(" ", "\\s")?);
+        assert!(match_pattern("abcd e", "\\s")?);
+        assert!(match_pattern("\t", "\\s")?);
+        assert!(match_pattern("\n", "\\s")?);
+        Ok(())
     }
 
     #[test]
-    fn match_character_groups() {
-        assert!(match_pattern("a", "[abc]"));
-        assert!(match_pattern("123cd5", "[abc]"));
-        assert!(match_pattern("12b2", "[abc]"));
-        assert!(!match_pattern("hello", "[abc]"));
+    fn match_negated_shorthands() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("abc1", "\\D")?);
+        assert!(!match_pattern("123", "\\D")?);
+        assert!(match_pattern("ab-c", "\\W")?);
+        assert!(!match_pattern("ab_c9", "\\W")?);
+        assert!(match_pattern("  x ", "\\S")?);
+        assert!(!match_pattern(" \t ", "\\S")?);
+        assert!(match_pattern("key: value", "^\\S+:\\s\\S+$")?);
+        Ok(())
     }
 
     #[test]
-    fn match_negative_character_groups() {
-        assert!(!match_pattern("a", "[^abc]"));
-        assert!(!match_pattern("cab", "[^abc]"));
-        assert!(match_pattern("scab", "[^abc]"));
-        assert!(match_pattern("123cd5", "[^abc]"));
-        assert!(match_pattern("12b2", "[^abc]"));
-        assert!(match_pattern("hello", "[^abc]"));
+    fn match_negated_shorthands_in_character_groups() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("a", "^[\\D\\s]$")?);
+        assert!(match_pattern(" ", "^[\\D\\s]$")?);
+        assert!(!match_pattern("5", "^[\\D\\s]$")?);
+        assert!(match_pattern("5", "^[\\W5]$")?);
+        assert!(match_pattern("-", "^[\\W5]$")?);
+        assert!(!match_pattern("x", "^[\\W5]$")?);
+        assert!(match_pattern(" ", "^[^\\S]$")?);
+        assert!(!match_pattern("x", "^[^\\S]$")?);
+        Ok(())
     }
 
     #[test]
-    fn match_consecutive_characters() {
-        assert!(match_pattern("hello world", "world"));
-        assert!(!match_pattern("hello worl d", "world"));
-        assert!(match_pattern("1 apple", "\\d apple"));
-        assert!(!match_pattern("1a apple", "\\d apple"));
-        assert!(match_pattern("100 apples", "\\d\\d\\d apple"));
-        assert!(!match_pattern("10 apples", "\\d\\d\\d apple"));
-        assert!(!match_pattern("two apples", "\\d\\d\\d apple"));
-        assert!(match_pattern("2 dogs", "\\d \\w\\w\\ws"));
-        assert!(match_pattern("3 cats", "\\d \\w\\w\\ws"));
-        assert!(!match_pattern("1 dog", "\\d \\w\\w\\ws"));
+    fn match_character_groups() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("a", "[abc]")?);
+        assert!(match_pattern("123cd5", "[abc]")?);
+        assert!(match_pattern("12b2", "[abc]")?);
+        assert!(!match_pattern("hello", "[abc]")?);
+        Ok(())
     }
 
     #[test]
-    fn match_consecutive_character_groups() {
-        assert!(match_pattern("ad", "[abc][def]"));
-        assert!(match_pattern("bf", "[abc][def]"));
-        assert!(!match_pattern("da", "[abc][def]"));
-        assert!(match_pattern("a 1z d", "[abc] \\d\\w [def]"));
-        assert!(!match_pattern("a 1z g", "[abc] \\d\\w [def]"));
+    fn match_negative_character_groups() -> Result<(), ParsePatternError> {
+        assert!(!match_pattern("a", "[^abc]")?);
+        assert!(!match_pattern("cab", "[^abc]")?);
+        assert!(match_pattern("scab", "[^abc]")?);
+        assert!(match_pattern("123cd5", "[^abc]")?);
+        assert!(match_pattern("12b2", "[^abc]")?);
+        assert!(match_pattern("hello", "[^abc]")?);
+        Ok(())
     }
 
     #[test]
-    fn match_complex_character_groups() {
-        assert!(match_pattern("9x", "[b\\d]x"));
-        assert!(match_pattern("2x", "[b\\d]x"));
-        assert!(match_pattern("bx", "[b\\d]x"));
-        assert!(!match_pattern("ax", "[b\\d]x"));
+    fn match_consecutive_characters() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("hello world", "world")?);
+        assert!(!match_pattern("hello worl d", "world")?);
+        assert!(match_pattern("1 apple", "\\d apple")?);
+        assert!(!match_pattern("1a apple", "\\d apple")?);
+        assert!(match_pattern("100 apples", "\\d\\d\\d apple")?);
+        assert!(!match_pattern("10 apples", "\\d\\d\\d apple")?);
+        assert!(!match_pattern("two apples", "\\d\\d\\d apple")?);
+        assert!(match_pattern("2 dogs", "\\d \\w\\w\\ws")?);
+        assert!(match_pattern("3 cats", "\\d \\w\\w\\ws")?);
+        assert!(!match_pattern("1 dog", "\\d \\w\\w\\ws")?);
+        Ok(())
+    }
 
-        assert!(match_pattern("9x", "[\\s\\d]x"));
-        assert!(match_pattern(" x", "[\\s\\d]x"));
-        assert!(match_pattern("\nx", "[\\s\\d]x"));
-        assert!(!match_pattern("ax", "[\\s\\d]x"));
+    #[test]
+    fn match_consecutive_character_groups() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("ad", "[abc][def]")?);
+        assert!(match_pattern("bf", "[abc][def]")?);
+        assert!(!match_pattern("da", "[abc][def]")?);
+        assert!(match_pattern("a 1z d", "[abc] \\d\\w [def]")?);
+        assert!(!match_pattern("a 1z g", "[abc] \\d\\w [def]")?);
+        Ok(())
     }
 
     #[test]
-    fn match_wildcard() {
-        assert!(match_pattern("dog", "d.g"));
-        assert!(match_pattern("dag", "d.g"));
-        assert!(match_pattern("dig", "d.g"));
-        assert!(!match_pattern("dung", "d.g"));
-        assert!(!match_pattern("dg", "d.g"));
-        assert!(!match_pattern("", "."));
+    fn match_complex_character_groups() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("9x", "[b\\d]x")?);
+        assert!(match_pattern("2x", "[b\\d]x")?);
+        assert!(match_pattern("bx", "[b\\d]x")?);
+        assert!(!match_pattern("ax", "[b\\d]x")?);
+
+        assert!(match_pattern("9x", "[\\s\\d]x")?);
+        assert!(match_pattern(" x", "[\\s\\d]x")?);
+        assert!(match_pattern("\nx", "[\\s\\d]x")?);
+        assert!(!match_pattern("ax", "[\\s\\d]x")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_character_ranges() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("m", "[a-z]")?);
+        assert!(!match_pattern("-", "[a-z]")?);
+        assert!(!match_pattern("M", "[a-z]")?);
+        assert!(match_pattern("_", "^[a-zA-Z0-9_]+$")?);
+        assert!(match_pattern("Snake_Case_9", "^[a-zA-Z0-9_]+$")?);
+        assert!(!match_pattern("kebab-case", "^[a-zA-Z0-9_]+$")?);
+        assert!(match_pattern("abc", "[^0-9]")?);
+        assert!(!match_pattern("123", "[^0-9]")?);
+        assert!(match_pattern("deadbeef", "^[0-9a-f]{8}$")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_literal_dash_and_bracket_in_character_group() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("-", "[-a]")?);
+        assert!(match_pattern("-", "[a-]")?);
+        assert!(!match_pattern("b", "[a-]")?);
+        assert!(match_pattern("b-c", "^[a-c-]+$")?);
+        assert!(match_pattern("]", "[]a]")?);
+        assert!(match_pattern("a", "[]a]")?);
+        assert!(!match_pattern("]", "[^]a]")?);
+        assert!(match_pattern("b", "[^]a]")?);
+        Ok(())
+    }
+
+    #[test]
+    fn report_invalid_character_range() {
+        assert_eq!(
+            compile_pattern("a[xz-a]").err(),
+            Some(InvalidCharacterRange(3))
+        );
+        assert_eq!(
+            compile_pattern("[^9-0]").err(),
+            Some(InvalidCharacterRange(2))
+        );
+        assert_eq!(
+            InvalidCharacterRange(3).render("a[xz-a]"),
+            "\
+Character range at col number 3 ends before it starts
+  |
+  | a[xz-a]
+  |    ^
+  = help: did you mean `a-z`?"
+        );
+    }
+
+    #[test]
+    fn match_posix_classes() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("a\tb", "a[[:space:]]b")?);
+        assert!(match_pattern("a b", "a[[:blank:]]b")?);
+        assert!(!match_pattern("ab", "a[[:space:]]b")?);
+        assert!(match_pattern("Hello", "^[[:upper:]][[:lower:]]+$")?);
+        assert!(!match_pattern("hello", "^[[:upper:]][[:lower:]]+$")?);
+        assert!(match_pattern("abc123", "^[[:alnum:]]+$")?);
+        assert!(!match_pattern("abc_123", "^[[:alnum:]]+$")?);
+        assert!(match_pattern("0xBEEF", "^0x[[:xdigit:]]+$")?);
+        assert!(match_pattern("hi!", "[[:punct:]]")?);
+        assert!(!match_pattern("hi", "[[:punct:]]")?);
+        assert!(match_pattern("42", "^[[:digit:]]+$")?);
+        assert!(match_pattern("a\x07", "[[:cntrl:]]")?);
+        assert!(match_pattern(" ", "[[:print:]]")?);
+        assert!(!match_pattern(" ", "[[:graph:]]")?);
+        assert!(match_pattern("é", "[^[:alpha:]]")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_posix_classes_alongside_other_members() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("_", "[[:digit:]a-f_]")?);
+        assert!(match_pattern("e", "[[:digit:]a-f_]")?);
+        assert!(match_pattern("7", "[[:digit:]a-f_]")?);
+        assert!(!match_pattern("g", "[[:digit:]a-f_]")?);
+        assert!(match_pattern("x y", "^[[:alpha:][:space:]]+$")?);
+        assert!(!match_pattern("x,y", "^[[:alpha:][:space:]]+$")?);
+        // an unclosed class is just a [ and : listed in the group
+        assert!(match_pattern(":", "[[:a]")?);
+        Ok(())
+    }
+
+    #[test]
+    fn report_unknown_posix_class() {
+        assert_eq!(
+            compile_pattern("a[b[:word:]]").err(),
+            Some(UnknownCharacterClass(3))
+        );
+    }
+
+    #[test]
+    fn match_escaped_metacharacters() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("1.5", r"^1\.5$")?);
+        assert!(!match_pattern("1x5", r"^1\.5$")?);
+        assert!(match_pattern("f(a)", r"\(a\)")?);
+        assert!(!match_pattern("fa", r"\(a\)")?);
+        assert!(match_pattern("a+b*c?", r"^a\+b\*c\?$")?);
+        assert!(match_pattern("[x]{2}", r"^\[x\]\{2\}$")?);
+        assert!(match_pattern("a|b", r"a\|b")?);
+        assert!(!match_pattern("a", r"a\|b")?);
+        assert!(match_pattern("^a$", r"\^a\$")?);
+        assert!(match_pattern("a$b", r"a\$")?);
+        assert!(match_pattern(r"C:\dir", r"C:\\dir")?);
+        // the $ is escaped, so this is an end anchor after a literal backslash
+        assert!(match_pattern(r"a\", r"a\\$")?);
+        assert!(!match_pattern(r"a\b", r"a\\$")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_escaped_metacharacters_in_character_group() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("]", r"[\]]")?);
+        assert!(match_pattern(r"\", r"[\\]")?);
+        assert!(!match_pattern("a", r"[\\]")?);
+        assert!(match_pattern("-", r"[a\-z]")?);
+        assert!(!match_pattern("m", r"[a\-z]")?);
+        assert!(match_pattern("^", r"[\^a]")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_character_escapes() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("a\tb", r"a\tb")?);
+        assert!(!match_pattern("a b", r"a\tb")?);
+        assert!(match_pattern("a\rb", r"a\rb")?);
+        assert!(match_pattern("\x0B\x0C", r"^\v\f$")?);
+        assert!(match_pattern("a\0", r"a\0")?);
+        assert!(match_pattern("AB", r"^\x41\x{42}$")?);
+        assert!(match_pattern("café", r"caf\u{e9}")?);
+        assert!(!match_pattern("cafe", r"caf\u{e9}")?);
+        assert!(match_pattern("😀😀", r"^\u{1F600}+$")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_character_escapes_in_character_group() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("a\tb", r"a[\t ]b")?);
+        assert!(match_pattern("a b", r"a[\t ]b")?);
+        assert!(match_pattern("\x07", r"[\x00-\x1F]")?);
+        assert!(!match_pattern(" ", r"[\x00-\x1F]")?);
+        assert!(match_pattern("é", r"^[\u{e0}-\u{ff}]$")?);
+        assert!(match_pattern("-", r"[\t-]")?);
+        assert_eq!(
+            compile_pattern(r"[\x{7F}-\x00]").err(),
+            Some(InvalidCharacterRange(1))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn report_invalid_code_point() {
+        assert_eq!(compile_pattern(r"a\x4").err(), Some(InvalidCodePoint(1)));
+        assert_eq!(
+            compile_pattern(r"a\u{D800}").err(),
+            Some(InvalidCodePoint(1))
+        );
+        assert_eq!(
+            compile_pattern(r"\u{110000}").err(),
+            Some(InvalidCodePoint(0))
+        );
+        assert_eq!(
+            compile_pattern(r"[ab\xZZ]").err(),
+            Some(InvalidCodePoint(3))
+        );
+        assert_eq!(
+            compile_pattern(r"[a-\u{zz}]").err(),
+            Some(InvalidCodePoint(3))
+        );
+    }
+
+    #[test]
+    fn report_unknown_escape() {
+        assert_eq!(compile_pattern(r"a\q").err(), Some(UnknownEscape(1)));
+        assert_eq!(compile_pattern(r"[a\q]").err(), Some(UnknownEscape(2)));
+        assert_eq!(compile_pattern(r"a\").err(), Some(TrailingBackslash(1)));
+        assert_eq!(
+            UnknownEscape(1).render(r"a\q"),
+            "\
+Unknown escape sequence at col number 1
+  |
+  | a\\q
+  |  ^
+  = help: did you mean `\\\\q` to match a backslash and `q`?"
+        );
+    }
+
+    #[test]
+    fn match_wildcard() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("dog", "d.g")?);
+        assert!(match_pattern("dag", "d.g")?);
+        assert!(match_pattern("dig", "d.g")?);
+        assert!(!match_pattern("dung", "d.g")?);
+        assert!(!match_pattern("dg", "d.g")?);
+        assert!(!match_pattern("", ".")?);
 
         // dot inside a character group should only match a literal dot
-        assert!(match_pattern(".", "[d.]"));
-        assert!(match_pattern("d", "[d.]"));
-        assert!(!match_pattern("a", "[d.]"));
+        assert!(match_pattern(".", "[d.]")?);
+        assert!(match_pattern("d", "[d.]")?);
+        assert!(!match_pattern("a", "[d.]")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_one_or_more_pattern() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("a", "a+")?);
+        assert!(match_pattern("aaaaa", "a+")?);
+        assert!(match_pattern("aaaaab", "a+")?);
+        assert!(match_pattern("bab", "a+")?);
+        assert!(match_pattern("aaab", "a+b")?);
+        assert!(match_pattern("aaab", "a+ab")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_multiple_one_or_more_patterns() -> Result<(), ParsePatternError> {
+        assert!(!match_pattern("a", "a+a+")?);
+        assert!(match_pattern("aa", "a+a+")?);
+        assert!(match_pattern("aaa", "a+a+")?);
+        assert!(match_pattern("abcdaab", "a+a+")?);
+        assert!(match_pattern("aaa", "a+a+a+")?);
+        assert!(match_pattern("aaaa", "a+a+a+")?);
+        Ok(())
     }
 
     #[test]
-    fn match_one_or_more_pattern() {
-        assert!(match_pattern("a", "a+"));
-        assert!(match_pattern("aaaaa", "a+"));
-        assert!(match_pattern("aaaaab", "a+"));
-        assert!(match_pattern("bab", "a+"));
-        assert!(match_pattern("aaab", "a+b"));
-        assert!(match_pattern("aaab", "a+ab"));
+    fn match_complex_one_or_more_pattern() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("bababa", "[ab]+")?);
+        assert!(match_pattern("b", "[ab]+")?);
+        assert!(match_pattern("ba", "[ab]+")?);
+        assert!(match_pattern("bab", "[ab]+ab")?);
+        assert!(match_pattern("aab", "[ab]+ab")?);
+        Ok(())
     }
 
     #[test]
-    fn match_multiple_one_or_more_patterns() {
-        assert!(!match_pattern("a", "a+a+"));
-        assert!(match_pattern("aa", "a+a+"));
-        assert!(match_pattern("aaa", "a+a+"));
-        assert!(match_pattern("abcdaab", "a+a+"));
-        assert!(match_pattern("aaa", "a+a+a+"));
-        assert!(match_pattern("aaaa", "a+a+a+"));
+    fn modifiers_backtrack_into_the_repeated_pattern() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("abc", "^(a|ab)+c$")?);
+        assert!(match_pattern("abc", "^(a|ab)*c$")?);
+        assert!(match_pattern("abc", "^(a|ab)?c$")?);
+        assert_eq!(
+            captured_texts("abab", "(ab|a)*b$"),
+            Some(vec![Some("abab".to_string()), Some("a".to_string())])
+        );
+        Ok(())
     }
 
     #[test]
-    fn match_complex_one_or_more_pattern() {
-        assert!(match_pattern("bababa", "[ab]+"));
-        assert!(match_pattern("b", "[ab]+"));
-        assert!(match_pattern("ba", "[ab]+"));
-        assert!(match_pattern("bab", "[ab]+ab"));
-        assert!(match_pattern("aab", "[ab]+ab"));
+    fn match_long_lines_without_overflowing_the_stack() -> Result<(), ParsePatternError> {
+        let line = "a".repeat(100_000);
+        assert!(match_pattern(&format!("{line}b"), ".*b")?);
+        assert!(match_pattern(&line, "a+$")?);
+        assert!(match_pattern(&line, "a{2,}$")?);
+        assert!(match_pattern(&line, "^a*?$")?);
+        assert_eq!(
+            captured_texts(&line, "^(a)*$").map(|groups| groups[1].clone()),
+            Some(Some("a".to_string()))
+        );
+
+        // patterns that can match each repetition in more than one way
+        let line = "ab".repeat(50_000);
+        assert!(match_pattern(&format!("{line}c"), "(a|b)*c")?);
+        assert!(match_pattern(&line, "(?:ab|b)*$")?);
+        assert!(match_pattern(&line, "(?:a|b)+$")?);
+        assert!(match_pattern(&format!("{line}z"), "(a|b){2,}z")?);
+        assert_eq!(
+            captured_texts(&line, "^(a|b)*$").map(|groups| groups[1].clone()),
+            Some(Some("b".to_string()))
+        );
+        Ok(())
     }
 
     #[test]
-    fn test_zero_or_one_pattern() {
-        assert!(match_pattern("", "a?"));
-        assert!(match_pattern("a", "a?"));
-        assert!(match_pattern("aa", "a?"));
-        assert!(match_pattern("b", "a?"));
+    fn test_zero_or_one_pattern() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("", "a?")?);
+        assert!(match_pattern("a", "a?")?);
+        assert!(match_pattern("aa", "a?")?);
+        assert!(match_pattern("b", "a?")?);
+        Ok(())
     }
 
     #[test]
-    fn test_complex_zero_or_one_pattern() {
-        assert!(!match_pattern("bd", "[bc]?cd"));
-        assert!(match_pattern("cd", "[bc]?cd"));
-        assert!(match_pattern("bcd", "[bc]?cd"));
+    fn test_complex_zero_or_one_pattern() -> Result<(), ParsePatternError> {
+        assert!(!match_pattern("bd", "[bc]?cd")?);
+        assert!(match_pattern("cd", "[bc]?cd")?);
+        assert!(match_pattern("bcd", "[bc]?cd")?);
+        Ok(())
     }
 
     #[test]
-    fn test_chaining_zero_or_one_patterns() {
-        assert!(!match_pattern("ac", "ab?bc"));
-        assert!(match_pattern("abc", "ab?bc"));
-        assert!(match_pattern("abbc", "ab?bc"));
-        assert!(!match_pattern("abbbc", "ab?bc"));
+    fn test_chaining_zero_or_one_patterns() -> Result<(), ParsePatternError> {
+        assert!(!match_pattern("ac", "ab?bc")?);
+        assert!(match_pattern("abc", "ab?bc")?);
+        assert!(match_pattern("abbc", "ab?bc")?);
+        assert!(!match_pattern("abbbc", "ab?bc")?);
 
-        assert!(!match_pattern("ac", "ab?b?bc"));
-        assert!(match_pattern("abc", "ab?b?bc"));
-        assert!(match_pattern("abbc", "ab?b?bc"));
-        assert!(match_pattern("abbbc", "ab?b?bc"));
-        assert!(!match_pattern("abbbbc", "ab?b?bc"));
+        assert!(!match_pattern("ac", "ab?b?bc")?);
+        assert!(match_pattern("abc", "ab?b?bc")?);
+        assert!(match_pattern("abbc", "ab?b?bc")?);
+        assert!(match_pattern("abbbc", "ab?b?bc")?);
+        assert!(!match_pattern("abbbbc", "ab?b?bc")?);
+        Ok(())
     }
 
     #[test]
-    fn test_alternating_patterns() {
-        assert!(match_pattern("dog", "(dog|cats)"));
-        assert!(match_pattern("cats", "(dog|cats)"));
-        assert!(match_pattern("dogs", "(dog|cats)s"));
-        assert!(match_pattern("aa", "(aa|a)a"));
-        assert!(match_pattern("aaa", "(aa|a)a"));
-        assert!(!match_pattern("a", "(aa|a)a"));
+    fn test_alternating_patterns() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("dog", "(dog|cats)")?);
+        assert!(match_pattern("cats", "(dog|cats)")?);
+        assert!(match_pattern("dogs", "(dog|cats)s")?);
+        assert!(match_pattern("aa", "(aa|a)a")?);
+        assert!(match_pattern("aaa", "(aa|a)a")?);
+        assert!(!match_pattern("a", "(aa|a)a")?);
 
         // ensures that our engine knows to select the second "aa" option,
         // even though selecting "a" allows the next "a" pattern to match (but not the "c" pattern)
-        assert!(match_pattern("aaac", "(a|aa)ac"));
-        
-        // todo: test combination with + and ?
+        assert!(match_pattern("aaac", "(a|aa)ac")?);
+
+        assert!(match_pattern("aaab", "(a|aa)+b")?);
+        assert!(match_pattern("ac", "a(b|c)?c")?);
+
+        // an option containing a quantifier gives back characters so the rest of the pattern can match
+        assert!(match_pattern("aa", "(a+|z)a")?);
+        assert!(match_pattern("abab", "(.*|x)b")?);
+        assert!(match_pattern("xaab", "(a*|b)ab")?);
+        assert!(match_pattern("aa", "a+|za")?);
+        let pattern = compile_pattern("(a+|z)(a+)")?;
+        let captures = pattern.captures_iter("aaa");
+        assert_eq!(
+            captures[0].get(1).map(|group| group.text.as_str()),
+            Some("aa")
+        );
+        assert_eq!(
+            captures[0].get(2).map(|group| group.text.as_str()),
+            Some("a")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn match_zero_or_more() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("ac", "ab*c")?);
+        assert!(match_pattern("abbbc", "ab*c")?);
+        assert!(!match_pattern("abbdc", "^ab*c")?);
+        assert!(match_pattern("aab", "^a*ab$")?);
+        assert!(match_pattern("ab", "^a*ab$")?);
+        assert!(!match_pattern("b", "^a*ab$")?);
+        assert!(match_pattern("anything at all", "^.*$")?);
+        assert!(match_pattern("", "^.*$")?);
+        assert!(match_pattern("start middle end", "^start.*end$")?);
+        assert!(!match_pattern("start middle", "^start.*end$")?);
+        assert!(match_pattern("x", "^(foo)*x$")?);
+        assert!(match_pattern("foofoox", "^(foo)*x$")?);
+        assert!(!match_pattern("fofoox", "^(foo)*x$")?);
+        assert!(match_pattern("key = value", "^\\w+\\s*=\\s*\\w*$")?);
+        assert!(match_pattern("aab", "^(a?)*b$")?);
+        Ok(())
+    }
+
+    #[test]
+    fn report_nothing_to_repeat_for_star() {
+        assert_eq!(compile_pattern("*a").err(), Some(NothingToRepeat(0)));
+        assert_eq!(compile_pattern("a|*").err(), Some(NothingToRepeat(2)));
+    }
+
+    #[test]
+    fn match_counted_repetition() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("call 555-1234", "\\d{3}-\\d{4}")?);
+        assert!(!match_pattern("call 55-1234", "\\d{3}-\\d{4}")?);
+        assert!(match_pattern("aaa", "^a{2,}$")?);
+        assert!(!match_pattern("a", "^a{2,}$")?);
+        assert!(match_pattern("ab", "^a{1,3}b$")?);
+        assert!(match_pattern("aaab", "^a{1,3}b$")?);
+        assert!(!match_pattern("aaaab", "^a{1,3}b$")?);
+        assert!(match_pattern("b", "^a{,2}b$")?);
+        assert!(match_pattern("abcabc!", "^(abc){2}!$")?);
+        assert!(!match_pattern("abc!", "^(abc){2}!$")?);
+        // backtracks so that the next pattern can match
+        assert!(match_pattern("aaaa", "^a{2,4}a{2}$")?);
+        assert!(!match_pattern("aaa", "^a{2,4}a{2}$")?);
+        Ok(())
     }
 
     #[test]
-    fn handle_unicode() {
-        assert!(match_pattern("#-×_=%-", "\\w"));
-        assert!(!match_pattern("%=#÷+×", "\\w"));
+    fn counted_repetition_backtracks_into_the_repeated_pattern() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("abc", "^(a|ab){1,2}c$")?);
+        assert!(match_pattern("aa", "(a+){2}")?);
+        assert!(!match_pattern("a", "(a+){2}")?);
+        assert!(match_pattern("aaa", "^(a*?){2}a$")?);
+        assert_eq!(
+            captured_texts("aaab", "^(a|aa){2}b"),
+            Some(vec![Some("aaab".to_string()), Some("aa".to_string())])
+        );
+        Ok(())
     }
 
     #[test]
-    fn handle_start_line_anchor() {
-        assert!(match_pattern("abcd", "^a"));
-        assert!(!match_pattern(" abcd", "^a"));
-        assert!(!match_pattern("baaaa", "^a"));
+    fn match_lazy_modifiers() -> Result<(), ParsePatternError> {
+        let first_match = |line: &str, pattern: &str| {
+            let captures = compile_pattern(pattern).unwrap().captures(line)?;
+            Some(captures.get(0)?.text.clone())
+        };
+        assert_eq!(first_match("<a><b>", "<.+?>").as_deref(), Some("<a>"));
+        assert_eq!(first_match("<a><b>", "<.+>").as_deref(), Some("<a><b>"));
+        assert_eq!(first_match("aaa", "a*?").as_deref(), Some(""));
+        assert_eq!(first_match("aaab", "a*?b").as_deref(), Some("aaab"));
+        assert_eq!(first_match("ab", "ab??").as_deref(), Some("a"));
+        assert_eq!(first_match("aaaa", "a{2,}?").as_deref(), Some("aa"));
+        assert_eq!(first_match("aaaa", "a{1,3}?a").as_deref(), Some("aa"));
+        assert!(!match_pattern("a", "^a{2,3}?$")?);
+        assert_eq!(
+            compile_pattern("a{3,1}?").err(),
+            Some(InvalidRepetitionBounds(1))
+        );
+        Ok(())
     }
 
     #[test]
-    fn handle_end_line_anchor() {
-        assert!(match_pattern("dog", "dog$"));
-        assert!(match_pattern("one dog", "dog$"));
-        assert!(!match_pattern("dogs", "dog$"));
-        assert!(!match_pattern("two dogs", "dog$"));
+    fn match_possessive_modifiers() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("aaab", "^a++b$")?);
+        assert!(!match_pattern("aaa", "^a++a$")?);
+        assert!(!match_pattern("aaa", "^a*+a$")?);
+        assert!(match_pattern("b", "^a*+b$")?);
+        assert!(!match_pattern("a", "^a?+a$")?);
+        assert!(match_pattern("ab", "^a?+b$")?);
+        assert!(!match_pattern("aaa", "^a{1,3}+a$")?);
+        assert!(match_pattern("aaaa", "^a{1,3}+a$")?);
+        assert!(match_pattern(r#""quoted""#, r#""[^"]*+""#)?);
+        Ok(())
     }
 
     #[test]
-    fn handle_start_and_end_anchors() {
-        assert!(match_pattern("dog", "^dog$"));
-        assert!(!match_pattern("dog dog", "^dog$"));
+    fn match_atomic_groups() -> Result<(), ParsePatternError> {
+        assert!(!match_pattern("abc", "^(?>a|ab)c$")?);
+        assert!(match_pattern("ac", "^(?>a|ab)c$")?);
+        assert!(!match_pattern("aaa", "^(?>a+)a$")?);
+        assert!(match_pattern("abab", "^(?>ab)+$")?);
+        assert_eq!(
+            captured_texts("xyz", "(?>(x))(y)"),
+            Some(vec![
+                Some("xy".to_string()),
+                Some("x".to_string()),
+                Some("y".to_string())
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn match_lookahead() -> Result<(), ParsePatternError> {
+        let pattern = compile_pattern(r"\d+(?= USD)")?;
+        let amounts: Vec<String> = pattern
+            .captures_iter("5 EUR, 42 USD, 7 USD")
+            .iter()
+            .map(|captures| captures.get(0).unwrap().text.clone())
+            .collect();
+        assert_eq!(amounts, vec!["42", "7"]);
+        assert!(match_pattern("foobaz", "^foo(?!bar)")?);
+        assert!(!match_pattern("foobar", "^foo(?!bar)")?);
+        assert!(match_pattern("password1", r"^(?=.*\d)(?=.*[a-z]).{8,}$")?);
+        assert!(!match_pattern("password", r"^(?=.*\d)(?=.*[a-z]).{8,}$")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_lookbehind() -> Result<(), ParsePatternError> {
+        let pattern = compile_pattern(r#"(?<!\\)""#)?;
+        let quotes: Vec<usize> = pattern
+            .captures_iter(r#""a \" b""#)
+            .iter()
+            .map(|captures| captures.get(0).unwrap().start)
+            .collect();
+        assert_eq!(quotes, vec![0, 7]);
+        assert!(match_pattern("costs $42", r"(?<=\$)\d+")?);
+        assert!(!match_pattern("costs 42", r"(?<=\$)\d+")?);
+        assert!(!match_pattern("b", "(?<=a)b")?);
+        assert!(match_pattern("xaab", "(?<=a+)b")?);
+        Ok(())
+    }
+
+    #[test]
+    fn anchors_in_lookbehind_see_the_whole_line() -> Result<(), ParsePatternError> {
+        assert!(!match_pattern("ab", "(?<=a$)b")?);
+        assert!(!match_pattern("ab", r"(?<=a\z)b")?);
+        assert!(!match_pattern("ab", r"(?<=a\b)b")?);
+        assert!(match_pattern("a b", r"(?<=a\b) b")?);
+        assert!(match_pattern("ab", "(?<=^a)b")?);
+        assert!(match_pattern("xa", "(?<=a$)")?);
+        assert!(match_pattern("ab", "(?<!a$)b")?);
+
+        // nothing inside a lookbehind matches past where it ends, even when it can't give characters back
+        assert!(match_pattern("a11", r"(?<=a\d++)1")?);
+        assert!(match_pattern("bb", "(?<=b*+)b")?);
+        assert!(match_pattern("aab", r"(?<=(a)\1)b")?);
+        assert!(!match_pattern("a1", r"(?<=a\d++)1")?);
+        // but lookaheads inside it can still see what follows
+        assert!(match_pattern("abc", "(?<=a(?=bc))b")?);
+        assert!(!match_pattern("abd", "(?<=a(?=bc))b")?);
+        Ok(())
+    }
+
+    #[test]
+    fn lookbehind_only_looks_back_as_far_as_it_can_match() -> Result<(), ParsePatternError> {
+        let line = format!("{}\"", "a".repeat(100_000));
+        let pattern = compile_pattern(r#"(?<!\\)""#)?;
+        assert_eq!(
+            pattern
+                .captures(&line)
+                .map(|captures| captures.get(0).unwrap().start),
+            Some(100_000)
+        );
+        assert!(match_pattern("xaab", "(?<=(a|aa){2})b")?);
+        assert!(!match_pattern("xab", "(?<=(a|aa){2})b")?);
+        Ok(())
+    }
+
+    #[test]
+    fn lookarounds_capture_groups() {
+        assert_eq!(
+            captured_texts("ab", "(?<=(a))b"),
+            Some(vec![Some("b".to_string()), Some("a".to_string())])
+        );
+        assert_eq!(
+            captured_texts("ab", "a(?=(b))"),
+            Some(vec![Some("a".to_string()), Some("b".to_string())])
+        );
+        assert_eq!(
+            captured_texts("ac", "a(?!(b))"),
+            Some(vec![Some("a".to_string()), None])
+        );
+    }
+
+    #[test]
+    fn match_word_boundaries() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("the cat sat", r"\bcat\b")?);
+        assert!(!match_pattern("concatenate", r"\bcat\b")?);
+        assert!(match_pattern("concatenate", r"\Bcat\B")?);
+        assert!(!match_pattern("cat", r"\Bcat")?);
+        assert!(match_pattern("a foo_bar", r"\<foo_bar\>")?);
+        assert!(!match_pattern("foo_bar", r"\<bar")?);
+        assert!(!match_pattern("foo_bar", r"foo\>")?);
+        assert!(match_pattern("x1 = 2", r"\w+\b = \b\d")?);
+        assert!(match_pattern("<a>", r"[\<]a[\>]")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_whole_words_with_word_regexp() -> Result<(), ParsePatternError> {
+        let options = CompileOptions {
+            word_regexp: true,
+            ..CompileOptions::default()
+        };
+        let word_match = |line: &str, pattern: &str| {
+            compile_pattern_with_options(pattern, options).map(|pattern| pattern.matches(line))
+        };
+        assert!(word_match("let count = 1", "count")?);
+        assert!(!word_match("let counter = 1", "count")?);
+        assert!(!word_match("let account = 1", "count")?);
+        assert!(word_match("counter count", "count")?);
+        // backtracks to find a match that ends at a word boundary
+        assert!(word_match("abc d", r"a\w*")?);
+        assert!(word_match("cd ab", "ab|cd")?);
+        assert!(word_match("ab", "^ab$")?);
+        assert!(!word_match("abc", "^ab")?);
+        // options of a top-level alternation give back characters to reach the word boundary
+        assert!(word_match("ab c-", "a.*|x")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_ignoring_case_with_option() -> Result<(), ParsePatternError> {
+        let options = CompileOptions {
+            ignore_case: true,
+            ..CompileOptions::default()
+        };
+        let case_match = |line: &str, pattern: &str| {
+            compile_pattern_with_options(pattern, options).map(|pattern| pattern.matches(line))
+        };
+        assert!(case_match("Hello World", "hello world")?);
+        assert!(case_match("HELLO", "h[a-z]+O")?);
+        assert!(case_match("Q", "[[:lower:]]")?);
+        assert!(!case_match("B", "[^a-c]")?);
+        assert!(case_match("ΣΟΦΟΣ", "σοφος")?);
+        assert!(case_match("σοφος", "ΣΟΦΟΣ")?);
+        assert!(case_match("ab AB", r"(\w+) \1")?);
+        assert!(case_match("A", "(?-i)a|A")?);
+        assert!(!case_match("a", "(?-i)A")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_inline_ignore_case_flags() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("FOO", "(?i)foo")?);
+        assert!(match_pattern("fOo", "f(?i)oo")?);
+        assert!(!match_pattern("Foo", "f(?i)oo")?);
+        // the flag applies to the alternatives after it
+        assert!(match_pattern("BAR", "(?i)foo|bar")?);
+        // but not outside the group it's in
+        assert!(match_pattern("abCC", "a(b(?i)c|x)C")?);
+        assert!(!match_pattern("abCc", "a(b(?i)c|x)C")?);
+        assert!(match_pattern("aBc", "a(?i:b)c")?);
+        assert!(!match_pattern("aBC", "a(?i:b)c")?);
+        assert!(match_pattern("aBc", "(?i)a(?-i:B)C")?);
+        assert!(!match_pattern("abc", "(?i)a(?-i:B)C")?);
+        assert!(match_pattern("Kelvin \u{212A}", "(?i)kelvin k")?);
+        // flags aren't capture groups
+        assert_eq!(
+            captured_texts("Ab", "(?i:a)(b)"),
+            Some(vec![Some("Ab".to_string()), Some("b".to_string())])
+        );
+        assert_eq!(
+            compile_pattern("(?i)*").map(|_| ()),
+            Err(NothingToRepeat(4))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn match_invalid_repetition_as_literal() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("a{", "a{")?);
+        assert!(match_pattern("{a}", "{a}")?);
+        assert!(match_pattern("a{1,x}", "a{1,x}")?);
+        assert!(match_pattern("}", "}")?);
+        Ok(())
+    }
+
+    #[test]
+    fn report_invalid_repetition() {
+        assert_eq!(
+            compile_pattern("ab{3,1}").err(),
+            Some(InvalidRepetitionBounds(2))
+        );
+        assert_eq!(
+            compile_pattern("a{99999999999999999999}").err(),
+            Some(InvalidRepetitionBounds(1))
+        );
+        assert_eq!(
+            compile_pattern("a{1,99999999999999999999}b").err(),
+            Some(InvalidRepetitionBounds(1))
+        );
+        assert_eq!(
+            InvalidRepetitionBounds(2).render("ab{3,1}"),
+            "\
+Repetition at col number 2 has a count that is too large, or a minimum count larger than its maximum count
+  |
+  | ab{3,1}
+  |   ^
+  = help: did you mean `{1,3}`?"
+        );
+        assert_eq!(
+            InvalidRepetitionBounds(1).render("a{99999999999999999999}"),
+            format!(
+                "\
+Repetition at col number 1 has a count that is too large, or a minimum count larger than its maximum count
+  |
+  | a{{99999999999999999999}}
+  |  ^
+  = help: counts can be at most {}",
+                usize::MAX
+            )
+        );
+        assert_eq!(compile_pattern("{2}").err(), Some(NothingToRepeat(0)));
+        assert_eq!(compile_pattern("a+{2}").err(), Some(NothingToRepeat(2)));
+    }
+
+    #[test]
+    fn match_many_alternatives() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("a bird", "a (cat|dog|bird)")?);
+        assert!(match_pattern("a dog", "a (cat|dog|bird)")?);
+        assert!(!match_pattern("a cow", "a (cat|dog|bird)")?);
+        assert!(match_pattern("abcd", "^(a|ab|abc)d$")?);
+        assert!(match_pattern("ad", "^(a|ab|abc)d$")?);
+        assert!(match_pattern("xyz", "x(a||y)z")?);
+        assert!(match_pattern("xz", "x(a||y)z")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_top_level_alternatives() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("error: oops", "error|warn")?);
+        assert!(match_pattern("warning: hmm", "error|warn")?);
+        assert!(!match_pattern("info: ok", "error|warn")?);
+        assert!(match_pattern("a dog", "^cat|dog$|bird")?);
+        assert!(match_pattern("cats", "^cat|dog$|bird")?);
+        assert!(!match_pattern("dogs", "^cat|dog$")?);
+        assert!(!match_pattern("a cat", "^cat|dog$")?);
+        assert!(match_pattern("a(b|c)", "(x|y)z|(b|c)")?);
+        Ok(())
+    }
+
+    #[test]
+    fn report_errors_in_alternatives() {
+        assert_eq!(compile_pattern("ab|c+*d").err(), Some(NothingToRepeat(5)));
+        assert_eq!(compile_pattern("(a|b|+)").err(), Some(NothingToRepeat(5)));
+        assert_eq!(compile_pattern("a|(b|c").err(), Some(UnmatchedBracket(2)));
+    }
+
+    #[test]
+    fn match_nested_groups() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("ac", "((a|b)c)")?);
+        assert!(match_pattern("bc", "((a|b)c)")?);
+        assert!(!match_pattern("cc", "((a|b)c)")?);
+        assert!(match_pattern("xabcy", "x(a(b)c)y")?);
+        assert!(match_pattern("a", "((((a))))")?);
+        assert!(match_pattern("cat dog", "(c(a(t)) (d(o(g))))")?);
+        assert!(!match_pattern("cat dot", "(c(a(t)) (d(o(g))))")?);
+        assert!(match_pattern("x", "(((a|b)|(c|d))|x)")?);
+        assert!(match_pattern("d", "(((a|b)|(c|d))|x)")?);
+        assert!(!match_pattern("e", "(((a|b)|(c|d))|x)")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_quantified_nested_groups() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("abcabc", "^(a(b)c)+$")?);
+        assert!(!match_pattern("abcab", "^(a(b)c)+$")?);
+        assert!(match_pattern("abbbcabc", "^(a(b)+c)+$")?);
+        assert!(match_pattern("xacbcz", "x((a|b)c)+z")?);
+        assert!(match_pattern("xz", "x((a|b)c)?z")?);
+        assert!(!match_pattern("xccz", "x((a|b)c)?z")?);
+        assert!(match_pattern("dogs and cats", "(dog(s)?) and (cat(s)?)")?);
+        Ok(())
+    }
+
+    #[test]
+    fn report_unmatched_nested_brackets() {
+        assert_eq!(compile_pattern("((a)").err(), Some(UnmatchedBracket(0)));
+        assert_eq!(compile_pattern("(a(b)").err(), Some(UnmatchedBracket(0)));
+        assert_eq!(compile_pattern("(a)(b(c)").err(), Some(UnmatchedBracket(3)));
+        assert_eq!(
+            compile_pattern("((a)))").err(),
+            Some(UnmatchedClosingBracket(5))
+        );
+        assert_eq!(
+            compile_pattern("((a|b)c|d+?*)").err(),
+            Some(NothingToRepeat(11))
+        );
+    }
+
+    #[test]
+    fn handle_unicode() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("#-×_=%-", "\\w")?);
+        assert!(!match_pattern("%=#÷+×", "\\w")?);
+        Ok(())
+    }
+
+    #[test]
+    fn handle_start_line_anchor() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("abcd", "^a")?);
+        assert!(!match_pattern(" abcd", "^a")?);
+        assert!(!match_pattern("baaaa", "^a")?);
+        Ok(())
+    }
+
+    #[test]
+    fn handle_end_line_anchor() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("dog", "dog$")?);
+        assert!(match_pattern("one dog", "dog$")?);
+        assert!(!match_pattern("dogs", "dog$")?);
+        assert!(!match_pattern("two dogs", "dog$")?);
+        Ok(())
+    }
+
+    #[test]
+    fn handle_start_and_end_anchors() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("dog", "^dog$")?);
+        assert!(!match_pattern("dog dog", "^dog$")?);
+        Ok(())
     }
 
     #[test]
@@ -354,7 +1510,7 @@ mod tests {
 
         fn assert_unmatched_bracket_at(pattern: &str, unmatched_bracket_idx: usize) {
             assert_eq!(
-                construct_pattern_from_str(pattern).err(),
+                compile_pattern(pattern).err(),
                 Some(UnmatchedBracket(unmatched_bracket_idx)),
                 "Failed for pattern: {}",
                 pattern
@@ -363,22 +1519,290 @@ mod tests {
     }
 
     #[test]
-    fn report_invalid_start_line_anchor() {
-        let error = construct_pattern_from_str("a^bcde").err();
-        assert_eq!(error, Some(InvalidStartLineAnchor(1)));
-        let error = construct_pattern_from_str(" ^ab[cde").err();
-        assert_eq!(error, Some(InvalidStartLineAnchor(1)));
-        let error = construct_pattern_from_str("[ab]^cde").err();
-        assert_eq!(error, Some(InvalidStartLineAnchor(4)));
+    fn report_empty_character_group() {
+        assert_eq!(compile_pattern("a[]").err(), Some(EmptyCharacterGroup(1)));
+        assert_eq!(compile_pattern("[^]b").err(), Some(EmptyCharacterGroup(0)));
+    }
+
+    #[test]
+    fn report_nothing_to_repeat() {
+        assert_eq!(compile_pattern("+a").err(), Some(NothingToRepeat(0)));
+        assert_eq!(compile_pattern("^?a").err(), Some(NothingToRepeat(1)));
+        assert_eq!(compile_pattern("a(?b)").err(), Some(NothingToRepeat(2)));
+        assert_eq!(compile_pattern("a+?*").err(), Some(NothingToRepeat(3)));
+    }
+
+    #[test]
+    fn report_unmatched_closing_bracket() {
+        assert_eq!(
+            compile_pattern("a)").err(),
+            Some(UnmatchedClosingBracket(1))
+        );
+        assert_eq!(
+            compile_pattern("(a))").err(),
+            Some(UnmatchedClosingBracket(3))
+        );
+    }
+
+    #[test]
+    fn render_error_with_caret() {
+        let pattern = "a*+?c";
+        let error = compile_pattern(pattern).err().unwrap();
+        let expected = "\
+Quantifier at col number 3 doesn't follow anything it can repeat
+  |
+  | a*+?c
+  |    ^
+  = help: did you mean `\\?`?";
+        assert_eq!(error.render(pattern), expected);
+        assert_eq!(
+            EmptyCharacterGroup(1).render("a[]"),
+            "\
+Character group at col number 1 must list at least one character
+  |
+  | a[]
+  |  ^
+  = help: did you mean `\\[\\]`?"
+        );
+    }
+
+    #[test]
+    fn match_anchors_anywhere() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("foo", "(^foo|bar$)")?);
+        assert!(match_pattern("a bar", "(^foo|bar$)")?);
+        assert!(!match_pattern("a foo", "(^foo|bar$)")?);
+        assert!(!match_pattern("bar a", "(^foo|bar$)")?);
+        assert!(match_pattern("y", "x|^y")?);
+        assert!(!match_pattern("ay", "x|^y")?);
+        assert!(!match_pattern("abcde", "a^bcde")?);
+        assert!(!match_pattern("abcde", "abcd$e")?);
+        assert!(match_pattern("ab", "a$|b")?);
+        assert!(match_pattern("a", "^(a|b)*$")?);
+        // backtracks so that the end anchor can match
+        assert!(match_pattern("a.b.c", r"^.*\.c$")?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_string_anchors() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("ab", r"\Aab\z")?);
+        assert!(!match_pattern("cab", r"\Aab")?);
+        assert!(match_pattern("ab\n", r"ab\Z")?);
+        assert!(!match_pattern("ab\n", r"ab\z")?);
+        assert!(match_pattern("b", r"a|\Ab")?);
+        Ok(())
+    }
+
+    fn captured_texts(line: &str, pattern: &str) -> Option<Vec<Option<String>>> {
+        let pattern = compile_pattern(pattern).unwrap();
+        let captures = pattern.captures(line)?;
+        Some(
+            (0..=pattern.group_count())
+                .map(|group| captures.get(group).map(|capture| capture.text.clone()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn capture_numbered_groups() {
+        let expected = vec![
+            Some("key=42".to_string()),
+            Some("key".to_string()),
+            Some("42".to_string()),
+        ];
+        assert_eq!(captured_texts("a key=42", r"(\w+)=(\d+)"), Some(expected));
+        assert_eq!(captured_texts("key=x", r"(\w+)=(\d+)"), None);
+    }
+
+    #[test]
+    fn capture_nested_groups_in_order_of_opening_bracket() {
+        let texts = captured_texts("abc", "((a)(b))(c)").unwrap();
+        let expected = ["abc", "ab", "a", "b", "c"].map(|text| Some(text.to_string()));
+        assert_eq!(texts, expected);
+    }
+
+    #[test]
+    fn capture_group_spans() {
+        let pattern = compile_pattern(r"é(\d+)").unwrap();
+        let captures = pattern.captures("xé123").unwrap();
+        let group = captures.get(1).unwrap();
+        assert_eq!((group.start, group.end), (2, 5));
+        let whole_match = captures.get(0).unwrap();
+        assert_eq!((whole_match.start, whole_match.end), (1, 5));
+    }
+
+    #[test]
+    fn groups_that_did_not_match_are_none() {
+        assert_eq!(
+            captured_texts("ac", "a(b)?c"),
+            Some(vec![Some("ac".to_string()), None])
+        );
+        assert_eq!(
+            captured_texts("b", "(a)|(b)"),
+            Some(vec![Some("b".to_string()), None, Some("b".to_string())])
+        );
+        // the first option captures a, which must be undone when c doesn't match after it
+        assert_eq!(
+            captured_texts("abd", "((a)c|ab)d"),
+            Some(vec![Some("abd".to_string()), Some("ab".to_string()), None])
+        );
+    }
+
+    #[test]
+    fn capture_last_repetition_of_group() {
+        assert_eq!(
+            captured_texts("abc!", r"(\w)+!"),
+            Some(vec![Some("abc!".to_string()), Some("c".to_string())])
+        );
+    }
+
+    #[test]
+    fn modifiers_in_groups_backtrack_for_the_rest_of_the_pattern() {
+        assert_eq!(
+            captured_texts("<a><b>", "<(.+)>"),
+            Some(vec![Some("<a><b>".to_string()), Some("a><b".to_string())])
+        );
+        assert_eq!(
+            captured_texts("<a><b>", "<(.+?)>"),
+            Some(vec![Some("<a>".to_string()), Some("a".to_string())])
+        );
+    }
+
+    #[test]
+    fn capture_every_match() {
+        let pattern = compile_pattern(r"(\d)x").unwrap();
+        let groups: Vec<String> = pattern
+            .captures_iter("1x 2y 3x")
+            .iter()
+            .map(|captures| captures.get(1).unwrap().text.clone())
+            .collect();
+        assert_eq!(groups, vec!["1", "3"]);
+        assert_eq!(compile_pattern("^a").unwrap().captures_iter("aaa").len(), 1);
+        assert_eq!(compile_pattern("b*").unwrap().captures_iter("ab").len(), 3);
+    }
+
+    #[test]
+    fn match_backreferences() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("the the cat", r"(\w+) \1")?);
+        assert!(match_pattern("cat and cat", r"(cat) and \1")?);
+        assert!(!match_pattern("cat and dog", r"(cat) and \1")?);
+        assert!(match_pattern(r#"say "hi""#, r#"(['"]).*\1"#)?);
+        assert!(match_pattern("say 'hi'", r#"(['"]).*\1"#)?);
+        assert!(!match_pattern(r#"say "hi'"#, r#"^[^'"]*(['"])[^'"]*\1$"#)?);
+        assert!(match_pattern(
+            "3 red squares and 3 red circles",
+            r"(\d+) (\w+) squares and \1 \2 circles"
+        )?);
+        assert!(!match_pattern(
+            "3 red squares and 4 red circles",
+            r"(\d+) (\w+) squares and \1 \2 circles"
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn match_backreference_after_backtracking() -> Result<(), ParsePatternError> {
+        // the group first captures "ab", which must be retried as "a" for the backreference to match
+        assert!(match_pattern("aba", "(a|ab)b\\1")?);
+        // a backreference to a group that didn't take part in the match doesn't match
+        assert!(!match_pattern("b", r"(a)?b\1")?);
+        assert!(match_pattern("aba", r"(a)?b\1")?);
+        // a repetition that only matches an empty string still records its group
+        assert!(match_pattern("a", r"(b?)?a\1")?);
+        assert!(match_pattern("a", r"(b*)*a\1")?);
+        assert!(match_pattern("a", r"(\b)*a\1")?);
+        assert_eq!(
+            captured_texts("a", "(b?)?a"),
+            Some(vec![Some("a".to_string()), Some(String::new())])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn report_invalid_backreference() {
+        assert_eq!(
+            compile_pattern(r"(a)\2").err(),
+            Some(InvalidBackreference(3))
+        );
+        assert_eq!(
+            compile_pattern(r"\1(a)").err(),
+            Some(InvalidBackreference(0))
+        );
+        assert!(compile_pattern(r"(a)|b\1").is_ok());
+    }
+
+    #[test]
+    fn capture_named_groups() {
+        let pattern = compile_pattern(r"(?<year>\d{4})-(?P<month>\d\d)").unwrap();
+        assert_eq!(pattern.group_count(), 2);
+        let captures = pattern.captures("on 2024-05").unwrap();
+        assert_eq!(captures.name("year").unwrap().text, "2024");
+        assert_eq!(captures.name("month").unwrap().text, "05");
+        assert_eq!(captures.get(2).unwrap().text, "05");
+        assert_eq!(captures.expand("${month}/${year}"), "05/2024");
+    }
+
+    #[test]
+    fn non_capturing_groups_are_not_numbered() -> Result<(), ParsePatternError> {
+        assert_eq!(
+            captured_texts("abc", "(?:a|x)(b)(?:c)"),
+            Some(vec![Some("abc".to_string()), Some("b".to_string())])
+        );
+        assert!(match_pattern("ababc", "^(?:ab)+c$")?);
+        assert!(match_pattern("abab", r"(?:x)?(ab)\1")?);
+        assert_eq!(compile_pattern("(?:a)(?<b>b)").unwrap().group_count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn backreference_to_named_group_uses_its_number() -> Result<(), ParsePatternError> {
+        assert!(match_pattern("abab", r"(?<pair>ab)\1")?);
+        Ok(())
+    }
+
+    #[test]
+    fn report_invalid_group_names() {
+        assert_eq!(compile_pattern("(?<>a)").err(), Some(InvalidGroupName(3)));
+        assert_eq!(
+            compile_pattern("a(?<1st>a)").err(),
+            Some(InvalidGroupName(4))
+        );
+        assert_eq!(
+            compile_pattern("(?P<a-b>a)").err(),
+            Some(InvalidGroupName(4))
+        );
+        assert_eq!(
+            compile_pattern("(?<name a)").err(),
+            Some(InvalidGroupName(3))
+        );
+        assert_eq!(
+            compile_pattern("(?<x>a)(?<x>b)").err(),
+            Some(DuplicateGroupName(10))
+        );
+    }
+
+    #[test]
+    fn report_errors_inside_named_groups() {
+        assert_eq!(compile_pattern("(?<x>a+*)").err(), Some(NothingToRepeat(7)));
+        assert_eq!(compile_pattern("(?:+)").err(), Some(NothingToRepeat(3)));
     }
 
     #[test]
-    fn report_invalid_end_line_anchor() {
-        let error = construct_pattern_from_str("abcd$e").err();
-        assert_eq!(error, Some(InvalidEndLineAnchor(4)));
-        let error = construct_pattern_from_str(" a$b[cde").err();
-        assert_eq!(error, Some(InvalidEndLineAnchor(2)));
-        let error = construct_pattern_from_str("[ab]c$de").err();
-        assert_eq!(error, Some(InvalidEndLineAnchor(5)));
+    fn describe_pattern_tree() {
+        let pattern = compile_pattern("^a+[bc](d|e)?$").unwrap();
+        let expected = "\
+Sequence
+  StartLineAnchor
+  OneOrMore
+    Character 'a'
+  Character 'b' or 'c'
+  ZeroOrOne
+    Group 1
+      Alternation
+        Character 'd'
+        Character 'e'
+  EndLineAnchor
+";
+        assert_eq!(pattern.tree().to_string(), expected);
     }
 }
@@ -1,3 +1,7 @@
+use crate::matching::ParsePatternError;
+use crate::matching::ParsePatternError::InvalidCodePoint;
+use std::num::ParseIntError;
+
 /// Split chars at the separator, returning a tuple of (chars before separator, chars after separator)
 /// Or None if the separator is not found
 pub fn split_at(chars: &[char], separator: char) -> Option<(&[char], &[char])> {
@@ -10,6 +14,157 @@ pub fn split_at(chars: &[char], separator: char) -> Option<(&[char], &[char])> {
     Some((before, after_with_separator_removed))
 }
 
+/// Split chars at the first separator that isn't escaped or nested inside a group or character group,
+/// returning a tuple of (chars before separator, chars after separator)
+/// Or None if there is no such separator, or a group or character group is never closed
+/// Use with ')' to find the end of a group, when chars start just after the group's opening bracket
+pub fn split_at_top_level(chars: &[char], separator: char) -> Option<(&[char], &[char])> {
+    let mut depth = 0;
+    let mut idx = 0;
+    while idx < chars.len() {
+        match chars[idx] {
+            char if char == separator && depth == 0 => {
+                return Some((&chars[..idx], &chars[idx + 1..]));
+            }
+            '\\' => idx += 1, // skip the escaped character
+            '[' => {
+                let (group, _) = split_at_character_group_end(&chars[idx + 1..])?;
+                idx += group.len() + 1;
+            }
+            '(' => depth += 1,
+            ')' if depth == 0 => return None, // the separator must be within the current group
+            ')' => depth -= 1,
+            _ => {}
+        }
+        idx += 1;
+    }
+    None
+}
+
+/// Split chars at the ']' that closes a character group, where chars start just after the opening '['
+/// A ']' listed first (after an optional '^') is a member of the group rather than the end of it
+/// Or None if the character group is never closed
+pub fn split_at_character_group_end(chars: &[char]) -> Option<(&[char], &[char])> {
+    let mut idx = match chars {
+        ['^', ']', ..] => 2,
+        [']', ..] => 1,
+        _ => 0,
+    };
+    while idx < chars.len() {
+        match chars[idx] {
+            ']' => return Some((&chars[..idx], &chars[idx + 1..])),
+            // skip over classes like [:alpha:], as they contain a ']'
+            '[' if chars.get(idx + 1) == Some(&':') => {
+                idx += match split_at_posix_class_end(&chars[idx + 2..]) {
+                    Some((name, _)) => name.len() + 4,
+                    None => 1,
+                }
+            }
+            '\\' => idx += 2, // skip the escaped character
+            _ => idx += 1,
+        }
+    }
+    None
+}
+
+/// Split chars at the ":]" that ends a POSIX class like [:alpha:], where chars start just after the "[:"
+/// Or None if the class is never closed
+pub fn split_at_posix_class_end(chars: &[char]) -> Option<(&[char], &[char])> {
+    let end_idx = chars.windows(2).position(|window| window == [':', ']'])?;
+    Some((&chars[..end_idx], &chars[end_idx + 2..]))
+}
+
+/// Whether a backslash followed by this char starts an escape for a single character, such as \t or \x41
+pub fn is_character_escape(char: char) -> bool {
+    matches!(char, 't' | 'n' | 'r' | 'f' | 'v' | '0' | 'x' | 'u')
+}
+
+/// Parse an escape for a single character, where chars start just after the backslash with a char
+/// accepted by is_character_escape, and `char_idx` is the col number of the backslash
+/// Code points can be written as \xHH with exactly two hex digits, or as \x{HHHH} or \u{HHHH}
+/// Returns the escaped character and the chars remaining after the escape
+pub fn parse_character_escape(
+    chars: &[char],
+    char_idx: usize,
+) -> Result<(char, &[char]), ParsePatternError> {
+    let (code_point, remaining) = match chars {
+        ['t', remaining @ ..] => return Ok(('\t', remaining)),
+        ['n', remaining @ ..] => return Ok(('\n', remaining)),
+        ['r', remaining @ ..] => return Ok(('\r', remaining)),
+        ['f', remaining @ ..] => return Ok(('\x0C', remaining)),
+        ['v', remaining @ ..] => return Ok(('\x0B', remaining)),
+        ['0', remaining @ ..] => return Ok(('\0', remaining)),
+        ['x' | 'u', '{', remaining @ ..] => {
+            let (digits, remaining) = split_at(remaining, '}').ok_or(InvalidCodePoint(char_idx))?;
+            (digits, remaining)
+        }
+        ['x', _, _, remaining @ ..] => (&chars[1..3], remaining),
+        _ => return Err(InvalidCodePoint(char_idx)),
+    };
+    // from_str_radix accepts a leading +, which isn't a hex digit
+    if code_point.is_empty() || !code_point.iter().all(char::is_ascii_hexdigit) {
+        return Err(InvalidCodePoint(char_idx));
+    }
+    let code_point: String = code_point.iter().collect();
+    u32::from_str_radix(&code_point, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .map(|char| (char, remaining))
+        .ok_or(InvalidCodePoint(char_idx))
+}
+
+/// Whether the name can be used to name a group, it must start with a letter or _ followed by letters, digits or _
+pub fn is_valid_group_name(name: &[char]) -> bool {
+    match name {
+        [first, rest @ ..] => {
+            (first.is_alphabetic() || *first == '_')
+                && rest
+                    .iter()
+                    .all(|char| char.is_alphanumeric() || *char == '_')
+        }
+        [] => false,
+    }
+}
+
+/// Split chars at every separator found by split_at_top_level
+pub fn split_all_at_top_level(chars: &[char], separator: char) -> Vec<&[char]> {
+    let mut parts = Vec::new();
+    let mut remaining = chars;
+    while let Some((before, after)) = split_at_top_level(remaining, separator) {
+        parts.push(before);
+        remaining = after;
+    }
+    parts.push(remaining);
+    parts
+}
+
+/// The (min, max) counts of a repetition and the chars after it, or the error from a count that is too large
+pub type RepetitionBounds<'a> = Result<(usize, Option<usize>, &'a [char]), ParseIntError>;
+
+/// Parse the bounds of a counted repetition like {2}, {2,}, {,5} or {2,5}, where chars start just after the '{'
+/// Returns (min, max, chars after the closing '}'), where a max of None means there is no maximum
+/// Or None if chars don't start with valid bounds, and an error if they do but a count is too large
+pub fn parse_repetition_bounds(chars: &[char]) -> Option<RepetitionBounds<'_>> {
+    let (bounds, remaining) = split_at(chars, '}')?;
+    // check for digits first, as parse also accepts a leading +
+    let parse_count = |digits: &[char]| -> Option<Result<usize, ParseIntError>> {
+        match !digits.is_empty() && digits.iter().all(char::is_ascii_digit) {
+            true => Some(digits.iter().collect::<String>().parse()),
+            false => None,
+        }
+    };
+    match split_at(bounds, ',') {
+        None => Some(parse_count(bounds)?.map(|count| (count, Some(count), remaining))),
+        Some(([], [])) => None,
+        Some(([], max)) => Some(parse_count(max)?.map(|max| (0, Some(max), remaining))),
+        Some((min, [])) => Some(parse_count(min)?.map(|min| (min, None, remaining))),
+        Some((min, max)) => {
+            let (min, max) = (parse_count(min)?, parse_count(max)?);
+            Some(min.and_then(|min| Ok((min, Some(max?), remaining))))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,6 +195,161 @@ mod tests {
         let result = split_at(&chars, ']');
         assert!(matches!(result, Some(([], []))));
 
-        assert!(matches!(split_at(&[], ']'), None));
+        assert!(split_at(&[], ']').is_none());
+    }
+
+    fn split_str(string: &str, separator: char) -> Option<(String, String)> {
+        let chars: Box<[char]> = string.chars().collect();
+        split_at_top_level(&chars, separator)
+            .map(|(before, after)| (before.iter().collect(), after.iter().collect()))
+    }
+
+    #[test]
+    fn test_split_at_top_level_skips_nested_groups() {
+        assert_eq!(
+            split_str("a(b)c)d", ')'),
+            Some(("a(b)c".to_string(), "d".to_string()))
+        );
+        assert_eq!(
+            split_str("((a|b)c)|d", '|'),
+            Some(("((a|b)c)".to_string(), "d".to_string()))
+        );
+        assert_eq!(split_str("(a|b)", '|'), None);
+    }
+
+    #[test]
+    fn test_split_at_top_level_skips_escapes_and_character_groups() {
+        assert_eq!(
+            split_str("\\)[)]a)b", ')'),
+            Some(("\\)[)]a".to_string(), "b".to_string()))
+        );
+        assert_eq!(split_str("[a|b", '|'), None);
+    }
+
+    #[test]
+    fn test_split_at_top_level_stops_at_end_of_group() {
+        assert_eq!(split_str("a)|b", '|'), None);
+        assert_eq!(split_str("(a", ')'), None);
+    }
+
+    #[test]
+    fn test_split_all_at_top_level() {
+        let chars: Box<[char]> = "a|(b|c)|d".chars().collect();
+        let parts: Vec<String> = split_all_at_top_level(&chars, '|')
+            .iter()
+            .map(|part| part.iter().collect())
+            .collect();
+        assert_eq!(parts, vec!["a", "(b|c)", "d"]);
+
+        let chars: Box<[char]> = "abc".chars().collect();
+        assert_eq!(split_all_at_top_level(&chars, '|'), vec![&chars[..]]);
+    }
+
+    type ParsedBounds = Result<(usize, Option<usize>, String), ParseIntError>;
+
+    fn parse_bounds_str(string: &str) -> Option<ParsedBounds> {
+        let chars: Box<[char]> = string.chars().collect();
+        parse_repetition_bounds(&chars).map(|bounds| {
+            bounds.map(|(min, max, remaining)| (min, max, remaining.iter().collect()))
+        })
+    }
+
+    #[test]
+    fn test_parse_repetition_bounds() {
+        assert_eq!(
+            parse_bounds_str("3}a"),
+            Some(Ok((3, Some(3), "a".to_string())))
+        );
+        assert_eq!(parse_bounds_str("3,}"), Some(Ok((3, None, "".to_string()))));
+        assert_eq!(
+            parse_bounds_str(",5}"),
+            Some(Ok((0, Some(5), "".to_string())))
+        );
+        assert_eq!(
+            parse_bounds_str("2,12}b"),
+            Some(Ok((2, Some(12), "b".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_repetition_bounds() {
+        assert_eq!(parse_bounds_str("3"), None);
+        assert_eq!(parse_bounds_str("}"), None);
+        assert_eq!(parse_bounds_str(",}"), None);
+        assert_eq!(parse_bounds_str("a}"), None);
+        assert_eq!(parse_bounds_str("1,2,3}"), None);
+        assert_eq!(parse_bounds_str(" 1}"), None);
+        assert_eq!(parse_bounds_str("99999999999999999999,x}"), None);
+    }
+
+    #[test]
+    fn test_parse_too_large_repetition_bounds() {
+        assert!(matches!(
+            parse_bounds_str("99999999999999999999}"),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            parse_bounds_str("1,99999999999999999999}"),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            parse_bounds_str("99999999999999999999,}"),
+            Some(Err(_))
+        ));
+    }
+
+    fn split_group_str(string: &str) -> Option<(String, String)> {
+        let chars: Box<[char]> = string.chars().collect();
+        split_at_character_group_end(&chars)
+            .map(|(before, after)| (before.iter().collect(), after.iter().collect()))
+    }
+
+    #[test]
+    fn test_split_at_character_group_end() {
+        let split = |before: &str, after: &str| Some((before.to_string(), after.to_string()));
+        assert_eq!(split_group_str("a-z]b"), split("a-z", "b"));
+        assert_eq!(split_group_str("]a]b"), split("]a", "b"));
+        assert_eq!(split_group_str("^]a]"), split("^]a", ""));
+        assert_eq!(split_group_str("\\]a]"), split("\\]a", ""));
+        assert_eq!(split_group_str("[:alpha:]]b"), split("[:alpha:]", "b"));
+        assert_eq!(split_group_str("a[:b]c"), split("a[:b", "c"));
+        assert_eq!(split_group_str("^]"), None);
+        assert_eq!(split_group_str("]"), None);
+        assert_eq!(split_group_str("abc"), None);
+    }
+
+    fn parse_escape_str(escape: &str) -> Result<(char, String), ParsePatternError> {
+        let chars: Vec<char> = escape.chars().collect();
+        parse_character_escape(&chars, 0)
+            .map(|(char, remaining)| (char, remaining.iter().collect()))
+    }
+
+    #[test]
+    fn test_parse_character_escape() {
+        assert_eq!(parse_escape_str("tab"), Ok(('\t', "ab".to_string())));
+        assert_eq!(parse_escape_str("0"), Ok(('\0', "".to_string())));
+        assert_eq!(parse_escape_str("x41x"), Ok(('A', "x".to_string())));
+        assert_eq!(parse_escape_str("x{1F600}"), Ok(('😀', "".to_string())));
+        assert_eq!(parse_escape_str("u{e9}b"), Ok(('é', "b".to_string())));
+        assert_eq!(parse_escape_str("x4"), Err(InvalidCodePoint(0)));
+        assert_eq!(parse_escape_str("x4g"), Err(InvalidCodePoint(0)));
+        assert_eq!(parse_escape_str("u00e9"), Err(InvalidCodePoint(0)));
+        assert_eq!(parse_escape_str("u{}"), Err(InvalidCodePoint(0)));
+        assert_eq!(parse_escape_str("u{+41}"), Err(InvalidCodePoint(0)));
+        assert_eq!(parse_escape_str("u{41"), Err(InvalidCodePoint(0)));
+        assert_eq!(parse_escape_str("u{D800}"), Err(InvalidCodePoint(0)));
+        assert_eq!(parse_escape_str("u{110000}"), Err(InvalidCodePoint(0)));
+        assert_eq!(parse_escape_str("u{FFFFFFFFFF}"), Err(InvalidCodePoint(0)));
+    }
+
+    #[test]
+    fn test_is_valid_group_name() {
+        let is_valid = |name: &str| is_valid_group_name(&name.chars().collect::<Vec<_>>());
+        assert!(is_valid("year"));
+        assert!(is_valid("_2nd_word"));
+        assert!(is_valid("été"));
+        assert!(!is_valid(""));
+        assert!(!is_valid("2nd"));
+        assert!(!is_valid("first-name"));
     }
 }
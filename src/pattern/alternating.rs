@@ -1,74 +1,177 @@
-use crate::pattern::match_struct::combine_match;
-use crate::pattern::{ChainablePattern, Match, Pattern};
-use crate::pattern::union_pattern::union;
+use crate::pattern::match_state::MatchState;
+use crate::pattern::{ChainablePattern, Match, Pattern, PatternTree};
+use std::cell::RefCell;
+use std::rc::Rc;
 
+/// Matches the first option that matches, so earlier options have priority over later options
 #[derive(Debug)]
 struct AlternatingPattern {
-    first_option: Box<dyn Pattern>,
-    second_option: Box<dyn Pattern>,
+    options: Vec<Box<dyn ChainablePattern>>,
 }
 
-pub fn alternating(
-    first_option: Box<dyn ChainablePattern>,
-    second_option: Box<dyn ChainablePattern>,
-) -> impl ChainablePattern {
-    AlternatingPattern {
-        first_option,
-        second_option,
-    }
+pub fn alternating(options: Vec<Box<dyn ChainablePattern>>) -> impl ChainablePattern {
+    AlternatingPattern { options }
 }
 
 impl Pattern for AlternatingPattern {
-    fn matches_exact(&self, chars: &[char]) -> Match {
-        self.first_option
-            .matches_exact(chars)
-            .match_or(self.second_option.matches_exact(chars))
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        self.options
+            .iter()
+            .map(|option| state.attempt(|state| option.matches_exact(chars, state)))
+            .find(Match::is_match)
+            .unwrap_or_default()
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::node(
+            "Alternation",
+            self.options.iter().map(|option| option.tree()).collect(),
+        )
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        self.options
+            .iter()
+            .try_fold(0, |width, option| Some(width.max(option.max_width()?)))
     }
 }
 
 impl ChainablePattern for AlternatingPattern {
+    // Chain the next pattern onto every option, so that each option can still try other ways of matching that
+    // allow the next pattern to match, e.g. a quantifier giving back characters
     fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern>
     where
         Self: Sized + 'static,
     {
+        let alternating_tree = self.tree();
+        let next_pattern = Rc::new(RefCell::new(pattern));
         Box::new(AlternatingPatternFollowedBy {
-            alternating_pattern: self,
-            next_pattern: pattern,
+            options: self
+                .options
+                .into_iter()
+                .map(|option| {
+                    option.followed_by(Box::new(AlternationEndPattern {
+                        next_pattern: next_pattern.clone(),
+                    }))
+                })
+                .collect(),
+            next_pattern,
+            alternating_tree,
         })
     }
 }
 
 #[derive(Debug)]
 struct AlternatingPatternFollowedBy {
-    alternating_pattern: AlternatingPattern,
-    next_pattern: Box<dyn Pattern>,
+    /// Each option, followed by an AlternationEndPattern that matches the next pattern
+    options: Vec<Box<dyn ChainablePattern>>,
+    /// The pattern to match after whichever option matches, shared by the end of every option
+    /// This is appended to by followed_by, after the options were created with a reference to this
+    next_pattern: Rc<RefCell<Box<dyn ChainablePattern>>>,
+    alternating_tree: PatternTree,
 }
 
 impl Pattern for AlternatingPatternFollowedBy {
-    fn matches_exact(&self, chars: &[char]) -> Match {
-        self.alternating_pattern
-            .first_option
-            .matches_exact(chars)
-            .and_then(|first_match| {
-                self.next_pattern
-                    .matches_exact(&chars[first_match.end..])
-                    .and_then(|second_match| combine_match(first_match, second_match))
-            })
-            .match_or(
-                self.alternating_pattern
-                    .second_option
-                    .matches_exact(chars)
-                    .and_then(|first_match| {
-                        self.next_pattern
-                            .matches_exact(&chars[first_match.end..])
-                            .and_then(|second_match| combine_match(first_match, second_match))
-                    }),
-            )
+    // try each option in order, until one of them allows the next pattern to match
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        self.options
+            .iter()
+            .map(|option| state.attempt(|state| option.matches_exact(chars, state)))
+            .find(Match::is_match)
+            .unwrap_or_default()
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::followed_by(
+            self.alternating_tree.clone(),
+            self.next_pattern.borrow().tree(),
+        )
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        // the end of each option doesn't count the next pattern, so it is only added once
+        self.options
+            .iter()
+            .try_fold(0, |width, option| Some(width.max(option.max_width()?)))?
+            .checked_add(self.next_pattern.borrow().max_width()?)
     }
 }
 
 impl ChainablePattern for AlternatingPatternFollowedBy {
     fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
-        union(self, pattern)
+        let next_pattern = self.next_pattern.take();
+        self.next_pattern.replace(next_pattern.followed_by(pattern));
+        Box::new(self)
+    }
+}
+
+/// Matches the next pattern at the end of an option
+#[derive(Debug)]
+struct AlternationEndPattern {
+    next_pattern: Rc<RefCell<Box<dyn ChainablePattern>>>,
+}
+
+impl Pattern for AlternationEndPattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        self.next_pattern.borrow().matches_exact(chars, state)
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::Empty
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        Some(0)
+    }
+}
+
+impl ChainablePattern for AlternationEndPattern {
+    // The end is always the end of an option, since the next pattern is appended to by the alternation
+    fn followed_by(self, _: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        unreachable!("patterns following an alternation are matched after each of its options")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::character_pattern::{literal, literal_str};
+    use crate::pattern::one_or_more;
+
+    #[test]
+    fn test_first_matching_option_has_priority() {
+        let pattern = alternating(vec![literal_str("a"), literal_str("ab"), literal_str("b")]);
+        assert_eq!(pattern.matches_exact_str("abc"), Match::at(0, 1));
+        assert_eq!(pattern.matches_exact_str("bc"), Match::at(0, 1));
+        assert_eq!(pattern.matches_exact_str("c"), Match::None);
+    }
+
+    #[test]
+    fn test_followed_by_tries_every_option() {
+        let pattern = alternating(vec![
+            literal_str("a"),
+            literal_str("ab"),
+            literal_str("abc"),
+        ])
+        .followed_by(literal_str("d"));
+        assert_eq!(pattern.matches_exact_str("ad"), Match::at(0, 2));
+        assert_eq!(pattern.matches_exact_str("abd"), Match::at(0, 3));
+        assert_eq!(pattern.matches_exact_str("abcd"), Match::at(0, 4));
+        assert_eq!(pattern.matches_exact_str("abce"), Match::None);
+    }
+
+    #[test]
+    fn test_options_backtrack_into_the_next_pattern() {
+        let options: Vec<Box<dyn ChainablePattern>> = vec![
+            Box::new(one_or_more(Box::new(literal('a')))),
+            literal_str("z"),
+        ];
+        let pattern = alternating(options)
+            .followed_by(literal_str("a"))
+            .followed_by(literal_str("b"));
+        assert_eq!(pattern.matches_exact_str("aab"), Match::at(0, 3));
+        assert_eq!(pattern.matches_exact_str("zab"), Match::at(0, 3));
+        assert_eq!(pattern.matches_exact_str("ab"), Match::None);
+        assert_eq!(pattern.max_width(), None);
     }
 }
@@ -1,13 +1,26 @@
-use crate::pattern::{ChainablePattern, Match, Pattern};
+use crate::pattern::match_state::MatchState;
 use crate::pattern::union_pattern::union;
+use crate::pattern::{ChainablePattern, Match, Pattern, PatternTree};
 
 #[derive(Debug)]
 struct AlwaysMatch {}
 
 impl Pattern for AlwaysMatch {
-    fn matches_exact(&self, _: &[char]) -> Match {
+    fn matches_exact(&self, _: &[char], _: &mut MatchState) -> Match {
         Match::at(0, 0)
     }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::Empty
+    }
+
+    fn matches_one_way(&self) -> bool {
+        true
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        Some(0)
+    }
 }
 
 impl ChainablePattern for AlwaysMatch {
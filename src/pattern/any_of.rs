@@ -0,0 +1,49 @@
+use crate::pattern::match_state::MatchState;
+use crate::pattern::{Match, Pattern, PatternTree};
+
+/// Matches when any of the patterns match, preferring the earliest pattern
+/// Unlike alternating, the patterns don't need to be chainable, so they can be whole compiled patterns
+#[derive(Debug)]
+struct AnyOfPattern {
+    patterns: Vec<Box<dyn Pattern>>,
+}
+
+impl Pattern for AnyOfPattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        self.patterns
+            .iter()
+            .map(|pattern| state.attempt(|state| pattern.matches_exact(chars, state)))
+            .find(Match::is_match)
+            .unwrap_or_default()
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::node(
+            "AnyOf",
+            self.patterns.iter().map(|pattern| pattern.tree()).collect(),
+        )
+    }
+}
+
+pub fn any_of(patterns: Vec<Box<dyn Pattern>>) -> impl Pattern {
+    AnyOfPattern { patterns }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::character_pattern::literal_str;
+    use crate::pattern::{start_line_anchor, ChainablePattern};
+
+    #[test]
+    fn test_any_of() {
+        let pattern = any_of(vec![
+            start_line_anchor().followed_by(literal_str("dog")),
+            Box::new(literal_str("cat")),
+        ]);
+        assert!(pattern.matches("dog"));
+        assert!(pattern.matches("a cat"));
+        assert!(!pattern.matches("a dog"));
+        assert!(!pattern.matches(""));
+    }
+}
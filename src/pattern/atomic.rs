@@ -0,0 +1,68 @@
+use crate::pattern::match_state::MatchState;
+use crate::pattern::union_pattern::union;
+use crate::pattern::{ChainablePattern, Match, Pattern, PatternTree};
+
+/// Matches the inner pattern once, committing to the first way it matches
+#[derive(Debug)]
+struct AtomicPattern {
+    inner_pattern: Box<dyn ChainablePattern>,
+}
+
+impl Pattern for AtomicPattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        self.inner_pattern.matches_exact(chars, state)
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::node("Atomic", vec![self.inner_pattern.tree()])
+    }
+
+    fn matches_one_way(&self) -> bool {
+        true
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        self.inner_pattern.max_width()
+    }
+}
+
+impl ChainablePattern for AtomicPattern {
+    // Deliberately doesn't chain the next pattern onto the inner pattern, so if the next pattern doesn't match
+    // the inner pattern isn't retried with a shorter match
+    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        union(self, pattern)
+    }
+}
+
+/// Match the pattern without ever backtracking into it, as an atomic group or possessive modifier
+pub fn atomic(pattern: Box<dyn ChainablePattern>) -> impl ChainablePattern {
+    AtomicPattern {
+        inner_pattern: pattern,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::character_pattern::{literal, literal_str};
+    use crate::pattern::{alternating, one_or_more};
+
+    #[test]
+    fn test_does_not_give_up_characters() {
+        let pattern =
+            atomic(Box::new(one_or_more(Box::new(literal('a'))))).followed_by(literal_str("a"));
+        assert_eq!(pattern.matches_exact_str("aaa"), Match::None);
+
+        let pattern =
+            atomic(Box::new(one_or_more(Box::new(literal('a'))))).followed_by(literal_str("b"));
+        assert_eq!(pattern.matches_exact_str("aab"), Match::at(0, 3));
+    }
+
+    #[test]
+    fn test_does_not_retry_other_options() {
+        let options = alternating(vec![literal_str("a"), literal_str("ab")]);
+        let pattern = atomic(Box::new(options)).followed_by(literal_str("c"));
+        assert_eq!(pattern.matches_exact_str("abc"), Match::None);
+        assert_eq!(pattern.matches_exact_str("ac"), Match::at(0, 2));
+    }
+}
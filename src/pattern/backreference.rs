@@ -0,0 +1,98 @@
+use crate::character_class::fold_case;
+use crate::pattern::match_state::MatchState;
+use crate::pattern::union_pattern::union;
+use crate::pattern::{ChainablePattern, Match, Pattern, PatternTree};
+
+/// Matches the text most recently captured by a group, as recorded in the MatchState
+/// Doesn't match if the group hasn't captured anything yet
+#[derive(Debug)]
+struct BackreferencePattern {
+    group: usize,
+    /// Whether the text can be in a different case from what was captured
+    ignore_case: bool,
+}
+
+impl Pattern for BackreferencePattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        let Some(group_match) = state.group(self.group) else {
+            return Match::None;
+        };
+        let captured = &state.input()[group_match.start..group_match.end];
+        let chars = state.matchable(chars);
+        let matches_captured = match self.ignore_case {
+            true => {
+                chars.len() >= captured.len()
+                    && captured
+                        .iter()
+                        .zip(chars)
+                        .all(|(captured, char)| fold_case(*captured) == fold_case(*char))
+            }
+            false => chars.starts_with(captured),
+        };
+        match matches_captured {
+            true => Match::at(0, captured.len()),
+            false => Match::None,
+        }
+    }
+
+    fn tree(&self) -> PatternTree {
+        match self.ignore_case {
+            true => PatternTree::leaf(format!("Backreference {} ignoring case", self.group)),
+            false => PatternTree::leaf(format!("Backreference {}", self.group)),
+        }
+    }
+
+    fn matches_one_way(&self) -> bool {
+        true
+    }
+}
+
+impl ChainablePattern for BackreferencePattern {
+    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        union(self, pattern)
+    }
+}
+
+pub fn backreference(group: usize) -> impl ChainablePattern {
+    BackreferencePattern {
+        group,
+        ignore_case: false,
+    }
+}
+
+/// Match the text captured by the group in any case, using Unicode simple case folding
+pub fn case_insensitive_backreference(group: usize) -> impl ChainablePattern {
+    BackreferencePattern {
+        group,
+        ignore_case: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::capture_group;
+    use crate::pattern::character_pattern::literal_str;
+
+    #[test]
+    fn test_matches_captured_text() {
+        let pattern = capture_group(1, literal_str("ab")).followed_by(Box::new(backreference(1)));
+        assert_eq!(pattern.matches_exact_str("abab"), Match::at(0, 4));
+        assert_eq!(pattern.matches_exact_str("abac"), Match::None);
+        assert_eq!(pattern.matches_exact_str("ab"), Match::None);
+    }
+
+    #[test]
+    fn test_matches_captured_text_ignoring_case() {
+        let pattern = capture_group(1, literal_str("ab"))
+            .followed_by(Box::new(case_insensitive_backreference(1)));
+        assert_eq!(pattern.matches_exact_str("abAB"), Match::at(0, 4));
+        assert_eq!(pattern.matches_exact_str("abA"), Match::None);
+    }
+
+    #[test]
+    fn test_group_without_capture_does_not_match() {
+        let pattern = backreference(1);
+        assert_eq!(pattern.matches_exact_str("a"), Match::None);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::pattern::match_state::MatchState;
+use crate::pattern::union_pattern::union;
+use crate::pattern::{ChainablePattern, Match, Pattern, PatternTree};
+
+/// Matches the inner pattern, recording where it matched as a numbered capture group
+#[derive(Debug)]
+struct CaptureGroupPattern {
+    group: usize,
+    inner_pattern: Box<dyn ChainablePattern>,
+}
+
+impl Pattern for CaptureGroupPattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        state.open_group(self.group, chars);
+        self.inner_pattern
+            .matches_exact(chars, state)
+            .and_then(|group_match| {
+                state.close_group(self.group, &chars[group_match.end..]);
+                group_match.into()
+            })
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::node(
+            format!("Group {}", self.group),
+            vec![self.inner_pattern.tree()],
+        )
+    }
+
+    fn matches_one_way(&self) -> bool {
+        self.inner_pattern.matches_one_way()
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        self.inner_pattern.max_width()
+    }
+}
+
+impl ChainablePattern for CaptureGroupPattern {
+    // Chain the next pattern onto the inner pattern, so that the inner pattern can still try other ways of
+    // matching that allow the next pattern to match, e.g. each option of an alternation
+    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern>
+    where
+        Self: Sized + 'static,
+    {
+        let tree = PatternTree::followed_by(self.tree(), pattern.tree());
+        let group_end = GroupEndPattern { group: self.group };
+        Box::new(CaptureGroupFollowedByPattern {
+            group: self.group,
+            chain: self
+                .inner_pattern
+                .followed_by(group_end.followed_by(pattern)),
+            tree,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct CaptureGroupFollowedByPattern {
+    group: usize,
+    /// The inner pattern, followed by the end of the group, followed by the next pattern
+    chain: Box<dyn ChainablePattern>,
+    tree: PatternTree,
+}
+
+impl Pattern for CaptureGroupFollowedByPattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        state.open_group(self.group, chars);
+        self.chain.matches_exact(chars, state)
+    }
+
+    fn tree(&self) -> PatternTree {
+        self.tree.clone()
+    }
+
+    fn matches_one_way(&self) -> bool {
+        self.chain.matches_one_way()
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        self.chain.max_width()
+    }
+}
+
+impl ChainablePattern for CaptureGroupFollowedByPattern {
+    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        Box::new(CaptureGroupFollowedByPattern {
+            group: self.group,
+            tree: PatternTree::followed_by(self.tree, pattern.tree()),
+            chain: self.chain.followed_by(pattern),
+        })
+    }
+}
+
+/// Matches an empty string where a capture group ends, recording the group's span
+#[derive(Debug)]
+struct GroupEndPattern {
+    group: usize,
+}
+
+impl Pattern for GroupEndPattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        state.close_group(self.group, chars);
+        Match::at(0, 0)
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::Empty
+    }
+
+    fn matches_one_way(&self) -> bool {
+        true
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        Some(0)
+    }
+}
+
+impl ChainablePattern for GroupEndPattern {
+    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        union(self, pattern)
+    }
+}
+
+/// Match the pattern as the numbered capture group, groups are numbered from 1 by their opening bracket
+pub fn capture_group(group: usize, pattern: Box<dyn ChainablePattern>) -> impl ChainablePattern {
+    CaptureGroupPattern {
+        group,
+        inner_pattern: pattern,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::character_pattern::literal_str;
+    use crate::pattern::match_struct::MatchIndices;
+    use crate::pattern::{alternating, zero_or_more};
+
+    #[test]
+    fn test_records_group() {
+        let pattern = literal_str("a").followed_by(Box::new(capture_group(1, literal_str("bc"))));
+        let input: Vec<char> = "abcd".chars().collect();
+        let mut state = MatchState::new(&input);
+        assert_eq!(pattern.matches_exact(&input, &mut state), Match::at(0, 3));
+        assert_eq!(state.group(1), Some(&MatchIndices { start: 1, end: 3 }));
+    }
+
+    #[test]
+    fn test_followed_by_retries_inner_options() {
+        let group = capture_group(
+            1,
+            Box::new(alternating(vec![literal_str("a"), literal_str("ab")])),
+        );
+        let pattern = group.followed_by(literal_str("c"));
+        let input: Vec<char> = "abc".chars().collect();
+        let mut state = MatchState::new(&input);
+        assert_eq!(pattern.matches_exact(&input, &mut state), Match::at(0, 3));
+        assert_eq!(state.group(1), Some(&MatchIndices { start: 0, end: 2 }));
+    }
+
+    #[test]
+    fn test_repeated_group_records_last_repetition() {
+        let pattern = zero_or_more(Box::new(capture_group(
+            1,
+            Box::new(alternating(vec![literal_str("a"), literal_str("b")])),
+        )));
+        let input: Vec<char> = "abac".chars().collect();
+        let mut state = MatchState::new(&input);
+        assert_eq!(pattern.matches_exact(&input, &mut state), Match::at(0, 3));
+        assert_eq!(state.group(1), Some(&MatchIndices { start: 2, end: 3 }));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::parse::split_at;
+use crate::pattern::match_state::MatchState;
+use crate::pattern::match_struct::MatchIndices;
+
+/// The text matched by a capture group and where it matched, with indices counted in chars of the line
+#[derive(Debug, PartialEq, Clone)]
+pub struct Capture {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// The groups captured by a match, where group 0 is the whole match
+/// Groups that didn't take part in the match, such as an optional group that matched zero times, are None
+#[derive(Debug, PartialEq, Clone)]
+pub struct Captures {
+    groups: Vec<Option<Capture>>,
+    /// The name of each group starting from group 1, or None if the group isn't named
+    group_names: Vec<Option<String>>,
+}
+
+impl Captures {
+    /// Collect the groups recorded in the state, `group_names` has an entry for each group in the pattern
+    pub fn new(
+        whole_match: &MatchIndices,
+        state: &MatchState,
+        group_names: &[Option<String>],
+    ) -> Captures {
+        let capture = |indices: &MatchIndices| Capture {
+            start: indices.start,
+            end: indices.end,
+            text: state.input()[indices.start..indices.end].iter().collect(),
+        };
+        let groups = (1..=group_names.len()).map(|group| state.group(group).map(capture));
+        Captures {
+            groups: std::iter::once(Some(capture(whole_match)))
+                .chain(groups)
+                .collect(),
+            group_names: group_names.to_vec(),
+        }
+    }
+
+    /// The capture of the group, or None if the group didn't take part in the match or doesn't exist
+    pub fn get(&self, group: usize) -> Option<&Capture> {
+        self.groups.get(group)?.as_ref()
+    }
+
+    /// The capture of the group with this name, or None if the group didn't take part in the match or doesn't exist
+    pub fn name(&self, name: &str) -> Option<&Capture> {
+        let group = self
+            .group_names
+            .iter()
+            .position(|group_name| group_name.as_deref() == Some(name))?;
+        self.get(group + 1)
+    }
+
+    /// Expand a replacement template, where `$N` or `${N}` is replaced by the text of group N,
+    /// `${name}` by the text of the named group, and `$$` by a single `$`
+    /// Groups that didn't take part in the match, or don't exist, are replaced by an empty string
+    pub fn expand(&self, template: &str) -> String {
+        let template: Box<[char]> = template.chars().collect();
+        let mut expanded = String::new();
+        let mut remaining = &template[..];
+        while let Some((char, rest)) = remaining.split_first() {
+            let reference = match (char, rest) {
+                ('$', ['$', rest @ ..]) => {
+                    expanded.push('$');
+                    remaining = rest;
+                    continue;
+                }
+                ('$', ['{', rest @ ..]) => split_at(rest, '}'),
+                ('$', [digit, ..]) if digit.is_ascii_digit() => {
+                    let digits = rest.iter().take_while(|char| char.is_ascii_digit()).count();
+                    Some(rest.split_at(digits))
+                }
+                _ => None,
+            };
+            match reference {
+                Some((reference, rest)) => {
+                    let reference: String = reference.iter().collect();
+                    let capture = match reference.parse() {
+                        Ok(group) => self.get(group),
+                        Err(_) => self.name(&reference),
+                    };
+                    expanded.extend(capture.map(|capture| capture.text.as_str()));
+                    remaining = rest;
+                }
+                // a $ that doesn't start a reference is a literal
+                None => {
+                    expanded.push(*char);
+                    remaining = rest;
+                }
+            }
+        }
+        expanded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collects_groups_from_state() {
+        let input: Vec<char> = "key=value".chars().collect();
+        let mut state = MatchState::new(&input);
+        state.open_group(2, &input[4..]);
+        state.close_group(2, &input[9..]);
+        let captures = Captures::new(&MatchIndices { start: 0, end: 9 }, &state, &[None, None]);
+        assert_eq!(
+            captures.get(0).map(|capture| capture.text.as_str()),
+            Some("key=value")
+        );
+        assert_eq!(captures.get(1), None);
+        assert_eq!(
+            captures.get(2),
+            Some(&Capture {
+                start: 4,
+                end: 9,
+                text: "value".to_string()
+            })
+        );
+        assert_eq!(captures.get(3), None);
+    }
+
+    fn key_value_captures() -> Captures {
+        let input: Vec<char> = "key=value".chars().collect();
+        let mut state = MatchState::new(&input);
+        state.open_group(1, &input);
+        state.close_group(1, &input[3..]);
+        state.open_group(2, &input[4..]);
+        state.close_group(2, &input[9..]);
+        let group_names = [Some("key".to_string()), Some("value".to_string()), None];
+        Captures::new(&MatchIndices { start: 0, end: 9 }, &state, &group_names)
+    }
+
+    #[test]
+    fn test_named_groups() {
+        let captures = key_value_captures();
+        assert_eq!(captures.name("key").map(|capture| capture.start), Some(0));
+        assert_eq!(captures.name("value").map(|capture| capture.start), Some(4));
+        assert_eq!(captures.name("other"), None);
+        assert_eq!(captures.get(3), None);
+    }
+
+    #[test]
+    fn test_expand_template() {
+        let captures = key_value_captures();
+        assert_eq!(captures.expand("$2=$1"), "value=key");
+        assert_eq!(captures.expand("${value}: ${key}"), "value: key");
+        assert_eq!(captures.expand("[${0}]"), "[key=value]");
+        assert_eq!(captures.expand("${1}s $1s"), "keys keys");
+        assert_eq!(captures.expand("$$1 costs $"), "$1 costs $");
+        assert_eq!(captures.expand("$3${missing}$9"), "");
+        assert_eq!(captures.expand("${unclosed"), "${unclosed");
+    }
+}
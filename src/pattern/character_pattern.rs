@@ -1,6 +1,13 @@
 use crate::character_class;
+use crate::character_class::posix_class;
 use crate::character_class::CharacterClass;
-use crate::pattern::{always_match, union_pattern, ChainablePattern, Match, Pattern};
+use crate::matching::ParsePatternError;
+use crate::matching::ParsePatternError::{
+    EmptyCharacterGroup, InvalidCharacterRange, UnknownCharacterClass, UnknownEscape,
+};
+use crate::parse::{is_character_escape, parse_character_escape, split_at_posix_class_end};
+use crate::pattern::match_state::MatchState;
+use crate::pattern::{always_match, union_pattern, ChainablePattern, Match, Pattern, PatternTree};
 
 #[derive(Debug)]
 struct CharacterPattern {
@@ -11,6 +18,11 @@ pub fn literal(char: char) -> impl ChainablePattern {
     character(Box::new(character_class::literal(char)))
 }
 
+/// Match the character in any case, using Unicode simple case folding
+pub fn case_insensitive_literal(char: char) -> impl ChainablePattern {
+    character(Box::new(character_class::literal(char).ignore_case()))
+}
+
 pub fn digits() -> impl ChainablePattern {
     character(Box::new(character_class::digits()))
 }
@@ -23,35 +35,104 @@ pub fn whitespace() -> impl ChainablePattern {
     character(Box::new(character_class::whitespace()))
 }
 
+pub fn non_digits() -> impl ChainablePattern {
+    character(Box::new(character_class::digits().negate()))
+}
+
+pub fn non_alphanumeric() -> impl ChainablePattern {
+    character(Box::new(character_class::alphanumeric().negate()))
+}
+
+pub fn non_whitespace() -> impl ChainablePattern {
+    character(Box::new(character_class::whitespace().negate()))
+}
+
 fn character(character_class: Box<dyn CharacterClass>) -> impl ChainablePattern {
     CharacterPattern { character_class }
 }
 
-/// Parse a character group pattern that supports matching any single character listed
-/// Chars must not be empty
-pub fn union(chars: &[char]) -> impl ChainablePattern {
-    character(match chars {
-        ['^', chars @ ..] => Box::new(
-            parse_character_group_pattern(chars)
-                .expect("chars must not be empty")
-                .negate(),
-        ),
-        _ => Box::new(parse_character_group_pattern(chars).expect("chars must not be empty")),
-    })
+/// Parse a character group pattern that supports matching any single character listed, or any character
+/// within a listed range such as a-z
+/// `char_idx` is the col number of the group's opening bracket, which isn't included in `chars`
+/// When `ignore_case` is set the members match in any case, and a negated group matches characters
+/// that match no member in any case
+pub fn union(
+    chars: &[char],
+    char_idx: usize,
+    ignore_case: bool,
+) -> Result<impl ChainablePattern, ParsePatternError> {
+    let (negated, members, members_idx) = match chars {
+        ['^', members @ ..] => (true, members, char_idx + 2),
+        _ => (false, chars, char_idx + 1),
+    };
+    let mut character_class = parse_character_group_pattern(members, members_idx)?
+        .ok_or(EmptyCharacterGroup(char_idx))?;
+    if ignore_case {
+        character_class = Box::new(character_class.ignore_case());
+    }
+    if negated {
+        character_class = Box::new(character_class.negate());
+    }
+    Ok(character(character_class))
 }
 
-fn parse_character_group_pattern(chars: &[char]) -> Option<Box<dyn CharacterClass>> {
-    let (pattern, remaining) = match chars {
+/// Parse the members of a character group, where `char_idx` is the col number of the first char
+/// Returns None if there are no members
+fn parse_character_group_pattern(
+    chars: &[char],
+    char_idx: usize,
+) -> Result<Option<Box<dyn CharacterClass>>, ParsePatternError> {
+    let Some((pattern, remaining)) = parse_character_group_member(chars, char_idx)? else {
+        return Ok(None);
+    };
+    let remaining_idx = char_idx + chars.len() - remaining.len();
+    Ok(Some(
+        match parse_character_group_pattern(remaining, remaining_idx)? {
+            None => pattern,
+            Some(other_character_class) => Box::new(pattern.union(other_character_class)),
+        },
+    ))
+}
+
+/// The character class of a character group member, along with the chars remaining after the member
+type CharacterGroupMember<'a> = (Box<dyn CharacterClass>, &'a [char]);
+
+/// Parse the first member of a character group, returning its character class and the remaining chars
+/// Returns None if there are no members
+fn parse_character_group_member(
+    chars: &[char],
+    char_idx: usize,
+) -> Result<Option<CharacterGroupMember<'_>>, ParsePatternError> {
+    // classes like [:alpha:] are only recognised when they are closed, otherwise the '[' is a literal
+    if let ['[', ':', class_chars @ ..] = chars {
+        if let Some((name, remaining)) = split_at_posix_class_end(class_chars) {
+            let name: String = name.iter().collect();
+            let class = posix_class(&name).ok_or(UnknownCharacterClass(char_idx))?;
+            return Ok(Some((class, remaining)));
+        }
+    }
+    if let Some(range) = parse_character_range(chars, char_idx)? {
+        return Ok(Some(range));
+    }
+    Ok(Some(match chars {
+        ['\\', char, ..] if is_character_escape(*char) => {
+            let (char, remaining) = parse_character_escape(&chars[1..], char_idx)?;
+            (Box::new(character_class::literal(char)), remaining)
+        }
         ['\\', char, remaining @ ..] => (
             match char {
                 'd' => Box::new(character_class::digits()) as Box<dyn CharacterClass>,
                 'w' => Box::new(character_class::alphanumeric()) as Box<dyn CharacterClass>,
                 's' => Box::new(character_class::whitespace()) as Box<dyn CharacterClass>,
-                // if escape isn't followed by an escaped character, assume it is a literal escape
-                _ => {
-                    Box::new(character_class::literal('\\').union(character_class::literal(*char)))
-                        as Box<dyn CharacterClass>
+                'D' => Box::new(character_class::digits().negate()) as Box<dyn CharacterClass>,
+                'W' => {
+                    Box::new(character_class::alphanumeric().negate()) as Box<dyn CharacterClass>
                 }
+                'S' => Box::new(character_class::whitespace().negate()) as Box<dyn CharacterClass>,
+                // like outside of a character group, escaping a character that isn't a letter or digit
+                // matches it literally, such as \] or \-
+                _ if !char.is_alphanumeric() => Box::new(character_class::literal(*char)),
+                _ => return Err(UnknownEscape(char_idx)),
             },
             remaining,
         ),
@@ -59,12 +140,45 @@ fn parse_character_group_pattern(chars: &[char]) -> Option<Box<dyn CharacterClas
             Box::new(character_class::literal(*char)) as Box<dyn CharacterClass>,
             remaining,
         ),
-        [] => None?,
+        [] => return Ok(None),
+    }))
+}
+
+/// Parse a single character in a character group, which is either a plain character or an escape like \t
+/// Returns None if chars don't start with a single character, e.g. they start with \d
+fn parse_group_character(
+    chars: &[char],
+    char_idx: usize,
+) -> Result<Option<(char, &[char])>, ParsePatternError> {
+    match chars {
+        ['\\', char, ..] if is_character_escape(*char) => {
+            parse_character_escape(&chars[1..], char_idx).map(Some)
+        }
+        ['\\', ..] | [] => Ok(None),
+        [char, remaining @ ..] => Ok(Some((*char, remaining))),
+    }
+}
+
+/// Parse a range of characters like a-z or \x00-\x1F, where `char_idx` is the col number of the first char
+/// Returns None if chars don't start with a range, in which case a - is a literal, e.g. at the end of the group
+fn parse_character_range(
+    chars: &[char],
+    char_idx: usize,
+) -> Result<Option<CharacterGroupMember<'_>>, ParsePatternError> {
+    let Some((start, ['-', after_dash @ ..])) = parse_group_character(chars, char_idx)? else {
+        return Ok(None);
+    };
+    let end_idx = char_idx + chars.len() - after_dash.len();
+    let Some((end, remaining)) = parse_group_character(after_dash, end_idx)? else {
+        return Ok(None);
     };
-    match parse_character_group_pattern(remaining) {
-        None => Some(pattern),
-        Some(other_character_class) => Some(Box::new(pattern.union(other_character_class))),
+    if end < start {
+        return Err(InvalidCharacterRange(char_idx));
     }
+    Ok(Some((
+        Box::new(character_class::range(start, end)),
+        remaining,
+    )))
 }
 
 #[allow(dead_code)] // this is useful for tests
@@ -78,8 +192,8 @@ pub fn literal_str(string: &str) -> Box<dyn ChainablePattern> {
 }
 
 impl Pattern for CharacterPattern {
-    fn matches_exact(&self, chars: &[char]) -> Match {
-        match chars.first() {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        match state.matchable(chars).first() {
             None => Match::None,
             Some(char) => match self.character_class.matches(*char) {
                 false => Match::None,
@@ -87,6 +201,18 @@ impl Pattern for CharacterPattern {
             },
         }
     }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::leaf(format!("Character {}", self.character_class.describe()))
+    }
+
+    fn matches_one_way(&self) -> bool {
+        true
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        Some(1)
+    }
 }
 
 impl ChainablePattern for CharacterPattern {
@@ -107,4 +233,13 @@ mod tests {
         assert_eq!(pattern.matches_exact_str("b"), Match::None);
         assert_eq!(pattern.matches_exact_str(""), Match::None);
     }
+
+    #[test]
+    fn test_union_ignoring_case() -> Result<(), ParsePatternError> {
+        let chars: Box<[char]> = "^a-c".chars().collect();
+        let pattern = union(&chars, 0, true)?;
+        assert_eq!(pattern.matches_exact_str("B"), Match::None);
+        assert_eq!(pattern.matches_exact_str("D"), Match::at(0, 1));
+        Ok(())
+    }
 }
@@ -1,42 +1,62 @@
-use crate::pattern::{ChainablePattern, Match, Pattern};
+use crate::pattern::match_state::MatchState;
+use crate::pattern::union_pattern::union;
+use crate::pattern::{ChainablePattern, Match, Pattern, PatternTree};
 
+/// Matches an empty string at the end of the line
 #[derive(Debug)]
-struct EndLineAnchor {
-    inner_pattern: Box<dyn Pattern>,
-}
+struct EndLineAnchor {}
 
 impl Pattern for EndLineAnchor {
-    // todo: this is inefficient, since matches will call this matches_exact function multiple times
-    // when really it could just be called once and process the pattern in reverse from the end of the string
-    fn matches_exact(&self, chars: &[char]) -> Match {
-        self.inner_pattern
-            .matches_exact(chars)
-            .and_then(|match_struct| {
-                if match_struct.end == chars.len() {
-                    match_struct.into()
-                } else {
-                    Match::None
-                }
-            })
+    fn matches_exact(&self, chars: &[char], _: &mut MatchState) -> Match {
+        match chars.is_empty() {
+            true => Match::at(0, 0),
+            false => Match::None,
+        }
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::leaf("EndLineAnchor")
+    }
+
+    fn matches_one_way(&self) -> bool {
+        true
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        Some(0)
     }
 }
 
-pub fn end_line_anchor(pattern: Box<dyn ChainablePattern>) -> impl Pattern {
-    EndLineAnchor {
-        inner_pattern: pattern,
+impl ChainablePattern for EndLineAnchor {
+    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        union(self, pattern)
     }
 }
 
+pub fn end_line_anchor() -> impl ChainablePattern {
+    EndLineAnchor {}
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::pattern::character_pattern::literal_str;
+    use crate::pattern::{one_or_more, wildcard};
 
     #[test]
-    fn test_start_line_anchor() {
-        let pattern = end_line_anchor(literal_str("ab"));
+    fn test_end_line_anchor() {
+        let pattern = literal_str("ab").followed_by(Box::new(end_line_anchor()));
         assert!(pattern.matches("cab"));
         assert!(pattern.matches("ab"));
         assert!(!pattern.matches("abc"));
     }
+
+    #[test]
+    fn test_repeat_backtracks_to_end_line_anchor() {
+        let pattern = one_or_more(Box::new(wildcard()))
+            .followed_by(literal_str("b"))
+            .followed_by(Box::new(end_line_anchor()));
+        assert!(pattern.matches("abab"));
+        assert!(!pattern.matches("abba"));
+    }
 }
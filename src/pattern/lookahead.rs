@@ -0,0 +1,93 @@
+use crate::pattern::match_state::MatchState;
+use crate::pattern::union_pattern::union;
+use crate::pattern::{ChainablePattern, Match, Pattern, PatternTree};
+
+/// Matches an empty string when the inner pattern matches the text that follows, or doesn't match it if negative
+#[derive(Debug)]
+struct LookaheadPattern {
+    inner_pattern: Box<dyn ChainablePattern>,
+    negative: bool,
+}
+
+impl Pattern for LookaheadPattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        let found = match self.negative {
+            // groups inside a negative lookahead never keep what they matched
+            true => {
+                let checkpoint = state.checkpoint();
+                let found =
+                    state.matching_ahead(|state| self.inner_pattern.matches_exact(chars, state));
+                state.restore(checkpoint);
+                found
+            }
+            false => state.attempt(|state| {
+                state.matching_ahead(|state| self.inner_pattern.matches_exact(chars, state))
+            }),
+        };
+        match found.is_match() != self.negative {
+            true => Match::at(0, 0),
+            false => Match::None,
+        }
+    }
+
+    fn tree(&self) -> PatternTree {
+        let label = match self.negative {
+            true => "NegativeLookahead",
+            false => "Lookahead",
+        };
+        PatternTree::node(label, vec![self.inner_pattern.tree()])
+    }
+
+    fn matches_one_way(&self) -> bool {
+        true
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        Some(0)
+    }
+}
+
+impl ChainablePattern for LookaheadPattern {
+    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        union(self, pattern)
+    }
+}
+
+/// Match where the pattern matches next, without including it in the match
+pub fn lookahead(pattern: Box<dyn ChainablePattern>) -> impl ChainablePattern {
+    LookaheadPattern {
+        inner_pattern: pattern,
+        negative: false,
+    }
+}
+
+/// Match where the pattern doesn't match next
+pub fn negative_lookahead(pattern: Box<dyn ChainablePattern>) -> impl ChainablePattern {
+    LookaheadPattern {
+        inner_pattern: pattern,
+        negative: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::character_pattern::literal_str;
+    use crate::pattern::{digits, one_or_more};
+
+    #[test]
+    fn test_lookahead() {
+        let pattern =
+            one_or_more(Box::new(digits())).followed_by(Box::new(lookahead(literal_str(" USD"))));
+        assert_eq!(pattern.matches_exact_str("42 USD"), Match::at(0, 2));
+        assert_eq!(pattern.matches_exact_str("42 EUR"), Match::None);
+    }
+
+    #[test]
+    fn test_negative_lookahead() {
+        let pattern = literal_str("a").followed_by(Box::new(negative_lookahead(literal_str("b"))));
+        assert_eq!(pattern.matches_exact_str("ac"), Match::at(0, 1));
+        assert_eq!(pattern.matches_exact_str("a"), Match::at(0, 1));
+        assert_eq!(pattern.matches_exact_str("ab"), Match::None);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::pattern::match_state::MatchState;
+use crate::pattern::union_pattern::union;
+use crate::pattern::{ChainablePattern, Match, Pattern, PatternTree};
+
+/// Matches an empty string when the inner pattern matches text that ends where this pattern is,
+/// or doesn't match it if negative
+#[derive(Debug)]
+struct LookbehindPattern {
+    /// The inner pattern, followed by LookbehindEndPattern so that it must end where this pattern is
+    inner_pattern: Box<dyn ChainablePattern>,
+    /// The most chars the inner pattern can match, or None if there is no limit
+    max_width: Option<usize>,
+    negative: bool,
+    tree: PatternTree,
+}
+
+impl LookbehindPattern {
+    /// Whether the inner pattern matches any of the text before `chars`, trying the closest start first
+    /// Only starts that are within the inner pattern's maximum width of `chars` are tried
+    fn matches_before(&self, chars: &[char], state: &mut MatchState) -> bool {
+        let line = state.input();
+        let end = state.position(chars);
+        let earliest_start = self
+            .max_width
+            .map_or(0, |max_width| end.saturating_sub(max_width));
+        (earliest_start..=end).rev().any(|start| {
+            state
+                .attempt_ending_at(end, |state| {
+                    self.inner_pattern.matches_exact(&line[start..], state)
+                })
+                .is_match()
+        })
+    }
+}
+
+impl Pattern for LookbehindPattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        let found = match self.negative {
+            // groups inside a negative lookbehind never keep what they matched
+            true => {
+                let checkpoint = state.checkpoint();
+                let found = self.matches_before(chars, state);
+                state.restore(checkpoint);
+                found
+            }
+            false => self.matches_before(chars, state),
+        };
+        match found != self.negative {
+            true => Match::at(0, 0),
+            false => Match::None,
+        }
+    }
+
+    fn tree(&self) -> PatternTree {
+        self.tree.clone()
+    }
+
+    fn matches_one_way(&self) -> bool {
+        true
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        Some(0)
+    }
+}
+
+impl ChainablePattern for LookbehindPattern {
+    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        union(self, pattern)
+    }
+}
+
+/// Matches an empty string where the lookbehind being matched is, which its inner pattern must end at
+#[derive(Debug)]
+struct LookbehindEndPattern {}
+
+impl Pattern for LookbehindEndPattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        match state.lookbehind_end() == Some(state.position(chars)) {
+            true => Match::at(0, 0),
+            false => Match::None,
+        }
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::Empty
+    }
+
+    fn matches_one_way(&self) -> bool {
+        true
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        Some(0)
+    }
+}
+
+impl ChainablePattern for LookbehindEndPattern {
+    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        union(self, pattern)
+    }
+}
+
+fn new_lookbehind(pattern: Box<dyn ChainablePattern>, negative: bool) -> LookbehindPattern {
+    let label = match negative {
+        true => "NegativeLookbehind",
+        false => "Lookbehind",
+    };
+    LookbehindPattern {
+        tree: PatternTree::node(label, vec![pattern.tree()]),
+        max_width: pattern.max_width(),
+        inner_pattern: pattern.followed_by(Box::new(LookbehindEndPattern {})),
+        negative,
+    }
+}
+
+/// Match where the pattern matches the text just before, without including it in the match
+pub fn lookbehind(pattern: Box<dyn ChainablePattern>) -> impl ChainablePattern {
+    new_lookbehind(pattern, false)
+}
+
+/// Match where the pattern doesn't match the text just before
+pub fn negative_lookbehind(pattern: Box<dyn ChainablePattern>) -> impl ChainablePattern {
+    new_lookbehind(pattern, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::character_pattern::{literal, literal_str};
+    use crate::pattern::{digits, one_or_more};
+
+    #[test]
+    fn test_lookbehind() {
+        let pattern =
+            lookbehind(literal_str("$")).followed_by(Box::new(one_or_more(Box::new(digits()))));
+        assert!(pattern.matches("costs $42"));
+        assert_eq!(
+            pattern
+                .find_from(&['$', '4', '2'], 0)
+                .map(|(indices, _)| indices.start),
+            Some(1)
+        );
+        assert!(!pattern.matches("costs 42"));
+    }
+
+    #[test]
+    fn test_lookbehind_must_end_where_it_is() {
+        let pattern =
+            lookbehind(Box::new(one_or_more(Box::new(literal('a'))))).followed_by(literal_str("b"));
+        assert!(pattern.matches("aab"));
+        assert!(!pattern.matches("acb"));
+    }
+
+    #[test]
+    fn test_lookbehind_width() {
+        assert_eq!(lookbehind(literal_str("ab")).max_width(), Some(0));
+        assert_eq!(new_lookbehind(literal_str("ab"), false).max_width, Some(2));
+        let pattern = new_lookbehind(Box::new(one_or_more(Box::new(literal('a')))), false);
+        assert_eq!(pattern.max_width, None);
+    }
+
+    #[test]
+    fn test_negative_lookbehind() {
+        let pattern = negative_lookbehind(literal_str("\\")).followed_by(literal_str("\""));
+        assert!(pattern.matches("say \"hi"));
+        assert!(!pattern.matches("say \\\"hi"));
+        assert!(pattern.matches("\""));
+    }
+}
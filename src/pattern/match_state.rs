@@ -0,0 +1,339 @@
+use crate::pattern::match_struct::MatchIndices;
+use crate::pattern::Match;
+
+/// A change to the recorded groups, along with the value it replaced so that it can be undone
+#[derive(Debug, Clone)]
+enum GroupChange {
+    Start {
+        group: usize,
+        previous: usize,
+    },
+    Span {
+        group: usize,
+        previous: Option<MatchIndices>,
+    },
+}
+
+/// The value a group was given while matching a repetition, which can be given to it again by `replay`
+#[derive(Debug, Clone)]
+enum GroupValue {
+    Start {
+        group: usize,
+        start: usize,
+    },
+    Span {
+        group: usize,
+        span: Option<MatchIndices>,
+    },
+}
+
+/// Where one way of matching a repeated pattern ended, along with the groups it recorded
+#[derive(Debug, Clone)]
+pub struct RepetitionEnd {
+    /// The index in the line where this way of matching ended
+    pub end: usize,
+    groups: Vec<GroupValue>,
+}
+
+/// State shared by every pattern while matching a line, recording where the capture groups matched
+/// Patterns are given the chars from where they start matching, which are always a suffix of the line,
+/// so the text before them can be found from the line and their position in it
+#[derive(Debug, Clone)]
+pub struct MatchState<'a> {
+    /// The whole line being matched
+    input: &'a [char],
+    /// Where each group that has been entered started matching, indexed by group number
+    group_starts: Vec<usize>,
+    /// The span of each group that has finished matching in the line, indexed by group number
+    groups: Vec<Option<MatchIndices>>,
+    /// Every change made to the groups in order, so that a failed attempt can undo just its own changes
+    changes: Vec<GroupChange>,
+    /// For each repeat finding the ways its pattern can match, innermost last, the checkpoint from before it
+    /// started matching and where each way found so far ended
+    repetitions: Vec<(usize, Vec<RepetitionEnd>)>,
+    /// Where the match of each lookbehind that is being matched must end, innermost last
+    /// None for a lookahead inside a lookbehind, whose match can go past where the lookbehind ends
+    lookbehind_ends: Vec<Option<usize>>,
+}
+
+impl<'a> MatchState<'a> {
+    pub fn new(input: &'a [char]) -> MatchState<'a> {
+        MatchState {
+            input,
+            group_starts: Vec::new(),
+            groups: Vec::new(),
+            changes: Vec::new(),
+            repetitions: Vec::new(),
+            lookbehind_ends: Vec::new(),
+        }
+    }
+
+    /// The index in the line of the first char of `chars`, which must be a suffix of the line
+    pub fn position(&self, chars: &[char]) -> usize {
+        self.input.len() - chars.len()
+    }
+
+    /// Record that a group started matching at the start of `chars`
+    pub fn open_group(&mut self, group: usize, chars: &[char]) {
+        if self.group_starts.len() <= group {
+            self.group_starts.resize(group + 1, 0);
+        }
+        let start = self.position(chars);
+        self.set_group_start(group, start);
+    }
+
+    fn set_group_start(&mut self, group: usize, start: usize) {
+        let previous = std::mem::replace(&mut self.group_starts[group], start);
+        self.changes.push(GroupChange::Start { group, previous });
+    }
+
+    /// Record that a group, which started matching at open_group, finished matching at the start of `chars`
+    pub fn close_group(&mut self, group: usize, chars: &[char]) {
+        if self.groups.len() <= group {
+            self.groups.resize(group + 1, None);
+        }
+        let span = MatchIndices {
+            start: self.group_starts[group],
+            end: self.position(chars),
+        };
+        self.set_group_span(group, Some(span));
+    }
+
+    fn set_group_span(&mut self, group: usize, span: Option<MatchIndices>) {
+        let previous = std::mem::replace(&mut self.groups[group], span);
+        self.changes.push(GroupChange::Span { group, previous });
+    }
+
+    /// The span in the line of the group's latest match, or None if the group hasn't matched
+    pub fn group(&self, group: usize) -> Option<&MatchIndices> {
+        self.groups.get(group)?.as_ref()
+    }
+
+    pub fn input(&self) -> &'a [char] {
+        self.input
+    }
+
+    /// Match with `f`, undoing any groups it recorded if it doesn't match
+    /// Patterns may leave the state changed when they don't match, so this must be used wherever a pattern
+    /// tries something else after a failed match, e.g. the next option of an alternation
+    pub fn attempt<F: FnOnce(&mut MatchState<'a>) -> Match>(&mut self, f: F) -> Match {
+        let checkpoint = self.checkpoint();
+        let result = f(self);
+        if !result.is_match() {
+            self.restore(checkpoint);
+        }
+        result
+    }
+
+    /// A point that the groups can be restored to, which stays valid until the state is restored to an earlier point
+    pub fn checkpoint(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Undo every change to the groups made since the checkpoint
+    pub fn restore(&mut self, checkpoint: usize) {
+        while self.changes.len() > checkpoint {
+            match self.changes.pop() {
+                Some(GroupChange::Start { group, previous }) => self.group_starts[group] = previous,
+                Some(GroupChange::Span { group, previous }) => self.groups[group] = previous,
+                None => break,
+            }
+        }
+    }
+
+    /// Find every way a repeated pattern can match with `f`, in the order they are found
+    /// `f` must call `record_repetition_end` at the end of each way of matching and then fail, so that the
+    /// pattern goes on to try its other ways, and the groups are left unchanged afterwards
+    pub fn repetition_ends<F: FnOnce(&mut MatchState<'a>) -> Match>(
+        &mut self,
+        f: F,
+    ) -> Vec<RepetitionEnd> {
+        let checkpoint = self.checkpoint();
+        self.repetitions.push((checkpoint, Vec::new()));
+        f(self);
+        self.restore(checkpoint);
+        let (_, ends) = self
+            .repetitions
+            .pop()
+            .expect("repetition_ends pushed this repetition");
+        ends
+    }
+
+    /// Record that the pattern of the innermost repeat in `repetition_ends` can match up to the start of `chars`,
+    /// along with the groups it recorded on the way
+    pub fn record_repetition_end(&mut self, chars: &[char]) {
+        let end = self.position(chars);
+        let (checkpoint, _) = self
+            .repetitions
+            .last()
+            .expect("repetition ends can only be recorded inside repetition_ends");
+        let groups = self.changes[*checkpoint..]
+            .iter()
+            .map(|change| match *change {
+                GroupChange::Start { group, .. } => GroupValue::Start {
+                    group,
+                    start: self.group_starts[group],
+                },
+                GroupChange::Span { group, .. } => GroupValue::Span {
+                    group,
+                    span: self.groups[group].clone(),
+                },
+            })
+            .collect();
+        if let Some((_, ends)) = self.repetitions.last_mut() {
+            ends.push(RepetitionEnd { end, groups });
+        }
+    }
+
+    /// Record the groups that were recorded by a way of matching a repeated pattern again, after restoring the
+    /// state to where that way started
+    pub fn replay(&mut self, repetition_end: &RepetitionEnd) {
+        for value in &repetition_end.groups {
+            match value {
+                GroupValue::Start { group, start } => self.set_group_start(*group, *start),
+                GroupValue::Span { group, span } => self.set_group_span(*group, span.clone()),
+            }
+        }
+    }
+
+    /// Match with `f` for a lookbehind whose match must end at index `end` in the line
+    /// No chars after `end` can be matched, but the whole line is still visible, so anchors and word boundaries
+    /// see the text after `end`
+    /// Any groups recorded are kept if it matches, and undone if it doesn't
+    pub fn attempt_ending_at<F: FnOnce(&mut MatchState<'a>) -> Match>(
+        &mut self,
+        end: usize,
+        f: F,
+    ) -> Match {
+        self.lookbehind_ends.push(Some(end));
+        let result = self.attempt(f);
+        self.lookbehind_ends.pop();
+        result
+    }
+
+    /// Match with `f` for a lookahead, which can match chars after the end of any lookbehind it is inside
+    pub fn matching_ahead<F: FnOnce(&mut MatchState<'a>) -> Match>(&mut self, f: F) -> Match {
+        self.lookbehind_ends.push(None);
+        let result = f(self);
+        self.lookbehind_ends.pop();
+        result
+    }
+
+    /// Where the match of the innermost lookbehind that is being matched must end
+    pub fn lookbehind_end(&self) -> Option<usize> {
+        self.lookbehind_ends.last().copied().flatten()
+    }
+
+    /// The chars from the start of `chars` that can be matched, which stop where the innermost lookbehind being
+    /// matched must end
+    pub fn matchable<'c>(&self, chars: &'c [char]) -> &'c [char] {
+        match self.lookbehind_end() {
+            Some(end) => &chars[..end.saturating_sub(self.position(chars)).min(chars.len())],
+            None => chars,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_groups_are_recorded_relative_to_the_line() {
+        let input: Vec<char> = "abcd".chars().collect();
+        let mut state = MatchState::new(&input);
+        assert_eq!(state.group(1), None);
+        state.open_group(1, &input[1..]);
+        state.close_group(1, &input[3..]);
+        assert_eq!(state.group(1), Some(&MatchIndices { start: 1, end: 3 }));
+        assert_eq!(state.group(2), None);
+    }
+
+    #[test]
+    fn test_attempt_undoes_failed_matches() {
+        let input: Vec<char> = "ab".chars().collect();
+        let mut state = MatchState::new(&input);
+        let result = state.attempt(|state| {
+            state.open_group(1, &input);
+            state.close_group(1, &input[1..]);
+            Match::None
+        });
+        assert_eq!(result, Match::None);
+        assert_eq!(state.group(1), None);
+
+        let result = state.attempt(|state| {
+            state.open_group(1, &input);
+            state.close_group(1, &input[1..]);
+            Match::at(0, 1)
+        });
+        assert_eq!(result, Match::at(0, 1));
+        assert_eq!(state.group(1), Some(&MatchIndices { start: 0, end: 1 }));
+    }
+
+    #[test]
+    fn test_restore_undoes_changes_since_checkpoint() {
+        let input: Vec<char> = "abc".chars().collect();
+        let mut state = MatchState::new(&input);
+        state.open_group(1, &input);
+        state.close_group(1, &input[1..]);
+        let checkpoint = state.checkpoint();
+        state.open_group(1, &input[1..]);
+        state.close_group(1, &input[3..]);
+        state.open_group(2, &input[2..]);
+        state.close_group(2, &input[3..]);
+        assert_eq!(state.group(1), Some(&MatchIndices { start: 1, end: 3 }));
+        state.restore(checkpoint);
+        assert_eq!(state.group(1), Some(&MatchIndices { start: 0, end: 1 }));
+        assert_eq!(state.group(2), None);
+    }
+
+    #[test]
+    fn test_replay_repetition_ends() {
+        let input: Vec<char> = "abc".chars().collect();
+        let mut state = MatchState::new(&input);
+        state.open_group(1, &input);
+        state.close_group(1, &input[1..]);
+        let ends = state.repetition_ends(|state| {
+            state.open_group(2, &input[1..]);
+            state.close_group(2, &input[2..]);
+            state.record_repetition_end(&input[2..]);
+            state.close_group(2, &input[3..]);
+            state.record_repetition_end(&input[3..]);
+            Match::None
+        });
+        assert_eq!(ends.iter().map(|end| end.end).collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(state.group(2), None);
+
+        let checkpoint = state.checkpoint();
+        state.replay(&ends[1]);
+        assert_eq!(state.group(1), Some(&MatchIndices { start: 0, end: 1 }));
+        assert_eq!(state.group(2), Some(&MatchIndices { start: 1, end: 3 }));
+        state.restore(checkpoint);
+        state.replay(&ends[0]);
+        assert_eq!(state.group(2), Some(&MatchIndices { start: 1, end: 2 }));
+    }
+
+    #[test]
+    fn test_attempt_ending_at_keeps_the_whole_line() {
+        let input: Vec<char> = "abcd".chars().collect();
+        let mut state = MatchState::new(&input);
+        assert_eq!(state.lookbehind_end(), None);
+        let result = state.attempt_ending_at(2, |state| {
+            assert_eq!(state.input().len(), 4);
+            assert_eq!(state.lookbehind_end(), Some(2));
+            assert_eq!(state.matchable(&input[1..]), ['b']);
+            assert_eq!(state.matchable(&input[3..]), [] as [char; 0]);
+            state.matching_ahead(|state| {
+                assert_eq!(state.lookbehind_end(), None);
+                assert_eq!(state.matchable(&input[1..]), ['b', 'c', 'd']);
+                Match::at(0, 0)
+            });
+            state.open_group(1, &input[1..]);
+            state.close_group(1, &input[2..]);
+            Match::at(0, 0)
+        });
+        assert_eq!(result, Match::at(0, 0));
+        assert_eq!(state.lookbehind_end(), None);
+        assert_eq!(state.group(1), Some(&MatchIndices { start: 1, end: 2 }));
+    }
+}
@@ -8,9 +8,10 @@ pub struct MatchIndices {
     pub end: usize,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
 pub enum Match {
     Match(MatchIndices),
+    #[default]
     None,
 }
 
@@ -41,11 +42,13 @@ impl Match {
             _ => self,
         }
     }
-}
 
-impl Default for Match {
-    fn default() -> Self {
-        Match::None
+    /// Return the match if there is one, or else call `f` to produce a match
+    pub fn or_else<F: FnOnce() -> Match>(self, f: F) -> Match {
+        match self {
+            Match::None => f(),
+            _ => self,
+        }
     }
 }
 
@@ -1,22 +1,52 @@
 mod alternating;
 mod always_match;
+mod any_of;
+mod atomic;
+mod backreference;
+mod capture_group;
+mod captures;
 mod character_pattern;
 mod end_line_anchor;
+mod lookahead;
+mod lookbehind;
+mod match_state;
 mod match_struct;
 mod one_or_more;
+#[allow(clippy::module_inception)]
 mod pattern;
+mod pattern_tree;
+mod repeat;
 mod start_line_anchor;
+mod string_anchor;
 mod union_pattern;
 mod wildcard;
+mod word_boundary;
+mod zero_or_more;
 mod zero_or_one;
 
 pub use alternating::alternating;
 pub use always_match::always_match;
-pub use character_pattern::{alphanumeric, digits, literal, union, whitespace};
+pub use any_of::any_of;
+pub use atomic::atomic;
+pub use backreference::{backreference, case_insensitive_backreference};
+pub use capture_group::capture_group;
+pub use captures::Captures;
+pub use character_pattern::{
+    alphanumeric, case_insensitive_literal, digits, literal, non_alphanumeric, non_digits,
+    non_whitespace, union, whitespace,
+};
 pub use end_line_anchor::end_line_anchor;
-pub use match_struct::Match;
+pub use lookahead::{lookahead, negative_lookahead};
+pub use lookbehind::{lookbehind, negative_lookbehind};
+pub use match_state::MatchState;
+pub use match_struct::{Match, MatchIndices};
 pub use one_or_more::one_or_more;
 pub use pattern::{ChainablePattern, Pattern};
+pub use pattern_tree::PatternTree;
+pub use repeat::{lazy_repeat, repeat};
 pub use start_line_anchor::start_line_anchor;
+pub use string_anchor::{string_end, string_end_before_newline, string_start};
 pub use wildcard::wildcard;
+pub use word_boundary::{not_word_boundary, word_boundary, word_end, word_start};
+pub use zero_or_more::zero_or_more;
 pub use zero_or_one::zero_or_one;
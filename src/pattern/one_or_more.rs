@@ -1,79 +1,17 @@
-use crate::pattern::match_struct::combine_match;
-use crate::pattern::{ChainablePattern, Match, Pattern};
-use crate::pattern::union_pattern::union;
-
-#[derive(Debug)]
-struct OneOrMorePattern {
-    inner_pattern: Box<dyn Pattern>,
-}
-
-impl Pattern for OneOrMorePattern {
-    fn matches_exact(&self, chars: &[char]) -> Match {
-        self.inner_pattern
-            .matches_exact(chars)
-            .and_then(
-                |first_match| match self.matches_exact(&chars[first_match.end..]) {
-                    Match::None => first_match.into(),
-                    Match::Match(second_match) => combine_match(first_match, &second_match),
-                },
-            )
-    }
-}
-
-impl ChainablePattern for OneOrMorePattern {
-    // Override followed by to ensure that OneOrMorePattern stops early enough to allow the next pattern to match
-    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern>
-    where
-        Self: Sized + 'static,
-    {
-        Box::new(OneOrMoreFollowedByPattern {
-            one_or_more: self,
-            second: pattern,
-        })
-    }
-}
-
-#[derive(Debug)]
-struct OneOrMoreFollowedByPattern {
-    one_or_more: OneOrMorePattern,
-    second: Box<dyn Pattern>,
-}
-
-impl Pattern for OneOrMoreFollowedByPattern {
-    // matches the one_or_more pattern as many times as possible, while still allowing the second pattern to match
-    fn matches_exact(&self, chars: &[char]) -> Match {
-        self.one_or_more
-            .inner_pattern
-            .matches_exact(chars)
-            .and_then(|first_match| {
-                let remaining = &chars[first_match.end..];
-                match self.matches_exact(remaining) {
-                    Match::None => self
-                        .second
-                        .matches_exact(remaining)
-                        .and_then(|second_match| combine_match(first_match, second_match)),
-                    Match::Match(second_match) => combine_match(first_match, &second_match),
-                }
-            })
-    }
-}
-
-impl ChainablePattern for OneOrMoreFollowedByPattern {
-    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
-        union(self, pattern)
-    }
-}
+use crate::pattern::repeat::quantifier;
+use crate::pattern::ChainablePattern;
 
+/// Match the pattern as many times as possible, at least once
 pub fn one_or_more(pattern: Box<dyn ChainablePattern>) -> impl ChainablePattern {
-    OneOrMorePattern {
-        inner_pattern: pattern,
-    }
+    quantifier(pattern, 1, None, "OneOrMore")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::pattern::character_pattern::{literal, literal_str};
+    use crate::pattern::{alternating, zero_or_one};
+    use crate::pattern::{Match, Pattern};
 
     #[test]
     fn test_simple_repeat() {
@@ -114,4 +52,36 @@ mod tests {
         assert_eq!(pattern.matches_exact_str("abab"), Match::at(0, 4));
         assert_eq!(pattern.matches_exact_str("ababab"), Match::at(0, 6));
     }
+
+    #[test]
+    fn test_repeat_of_empty_match_terminates() {
+        let pattern = one_or_more(Box::new(zero_or_one(Box::new(literal('a')))));
+        assert_eq!(pattern.matches_exact_str("aab"), Match::at(0, 2));
+        assert_eq!(pattern.matches_exact_str("b"), Match::at(0, 0));
+
+        let pattern = one_or_more(Box::new(zero_or_one(Box::new(literal('a')))))
+            .followed_by(literal_str("b"));
+        assert_eq!(pattern.matches_exact_str("aab"), Match::at(0, 3));
+        assert_eq!(pattern.matches_exact_str("c"), Match::None);
+    }
+
+    #[test]
+    fn test_repeat_backtracks_into_inner_pattern() {
+        let options = alternating(vec![literal_str("a"), literal_str("ab")]);
+        let pattern = one_or_more(Box::new(options)).followed_by(literal_str("c"));
+        assert_eq!(pattern.matches_exact_str("abc"), Match::at(0, 3));
+        assert_eq!(pattern.matches_exact_str("aabac"), Match::at(0, 5));
+    }
+
+    #[test]
+    fn test_long_repeat_of_alternation() {
+        let line = "ab".repeat(50_000);
+        let options = alternating(vec![literal_str("a"), literal_str("b")]);
+        let pattern = one_or_more(Box::new(options)).followed_by(literal_str("c"));
+        assert_eq!(
+            pattern.matches_exact_str(&format!("{line}c")),
+            Match::at(0, line.len() + 1)
+        );
+        assert_eq!(pattern.matches_exact_str("abab"), Match::None);
+    }
 }
@@ -1,22 +1,65 @@
-use crate::pattern::{always_match, Match};
+use crate::pattern::match_state::MatchState;
+use crate::pattern::match_struct::MatchIndices;
+use crate::pattern::{always_match, Match, PatternTree};
 use std::fmt::Debug;
 
 pub trait Pattern: Debug {
     /// Whether the Pattern matches starting from the first character
-    fn matches_exact(&self, chars: &[char]) -> Match;
+    /// `chars` is a suffix of the line in `state`, where capture groups record their matches
+    /// Patterns that look at the text before `chars`, such as lookbehind, find it from the line in `state`
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match;
+
+    /// Describe the structure of the pattern, used by --debug-pattern
+    fn tree(&self) -> PatternTree;
+
+    /// Whether the pattern can match in at most one way from any position, so trying it again can never
+    /// help the patterns after it match
+    /// Repeats of these patterns repeat in a loop instead of backtracking into each repetition
+    fn matches_one_way(&self) -> bool {
+        false
+    }
+
+    /// The most chars the pattern can match, or None if there is no limit, such as for a backreference
+    /// Used by lookbehind to limit how far back it looks
+    fn max_width(&self) -> Option<usize> {
+        None
+    }
 
     /// Whether the Pattern matches starting from the first character of the string
     fn matches_exact_str(&self, string: &str) -> Match {
         let chars: Box<[char]> = string.chars().collect();
-        self.matches_exact(&chars)
+        self.matches_exact(&chars, &mut MatchState::new(&chars))
+    }
+
+    /// Find the first match that starts at or after `start`, along with the state recording its capture groups
+    /// The returned indices are relative to the start of `chars`
+    fn find_from<'a>(
+        &self,
+        chars: &'a [char],
+        start: usize,
+    ) -> Option<(MatchIndices, MatchState<'a>)> {
+        // the state is reused for each start, so that the memory it needs is only allocated once per line
+        let mut state = MatchState::new(chars);
+        // use =chars.len() so that when the string has zero length it will still check if the pattern matches
+        // a pattern like "a?" can match an empty string
+        for i in start..=chars.len() {
+            if let Match::Match(indices) = self.matches_exact(&chars[i..], &mut state) {
+                let indices = MatchIndices {
+                    start: i,
+                    end: i + indices.end,
+                };
+                return Some((indices, state));
+            }
+            // patterns may leave groups recorded when they don't match
+            state.restore(0);
+        }
+        None
     }
 
     /// Whether the Pattern matches starting from any character in the string
     fn matches(&self, string: &str) -> bool {
         let chars: Box<[char]> = string.chars().collect();
-        // use =chars.len() so that when the string has zero length it will still check if the pattern matches
-        // a pattern like "a?" can match an empty string
-        (0..=chars.len()).any(|i| self.matches_exact(&chars[i..]).is_match())
+        self.find_from(&chars, 0).is_some()
     }
 }
 
@@ -30,9 +73,11 @@ pub trait ChainablePatternBoxed {
 }
 
 /// A pattern that can be followed by or preceded by another pattern
-/// Examples of Patterns that aren't chainable are the start and end line anchor patterns
+/// Examples of Patterns that aren't chainable are any_of and compiled patterns, which are only matched on their own
 pub trait ChainablePattern: Pattern + ChainablePatternBoxed {
     /// Create a new pattern that matches when this pattern and the next pattern match consecutively
+    /// Chains append the next pattern to their last pattern, so that every pattern in the chain that can match in
+    /// more than one way can still try another way when the next pattern doesn't match
     fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern>;
 }
 
@@ -46,28 +91,68 @@ impl<T: ChainablePattern> ChainablePatternBoxed for T {
 }
 
 impl Pattern for Box<dyn Pattern> {
-    fn matches_exact(&self, chars: &[char]) -> Match {
-        (**self).matches_exact(chars)
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        (**self).matches_exact(chars, state)
+    }
+
+    fn tree(&self) -> PatternTree {
+        (**self).tree()
+    }
+
+    fn matches_one_way(&self) -> bool {
+        (**self).matches_one_way()
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        (**self).max_width()
     }
 
     fn matches_exact_str(&self, string: &str) -> Match {
         (**self).matches_exact_str(string)
     }
 
+    fn find_from<'a>(
+        &self,
+        chars: &'a [char],
+        start: usize,
+    ) -> Option<(MatchIndices, MatchState<'a>)> {
+        (**self).find_from(chars, start)
+    }
+
     fn matches(&self, string: &str) -> bool {
         (**self).matches(string)
     }
 }
 
 impl Pattern for Box<dyn ChainablePattern> {
-    fn matches_exact(&self, chars: &[char]) -> Match {
-        (**self).matches_exact(chars)
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        (**self).matches_exact(chars, state)
+    }
+
+    fn tree(&self) -> PatternTree {
+        (**self).tree()
+    }
+
+    fn matches_one_way(&self) -> bool {
+        (**self).matches_one_way()
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        (**self).max_width()
     }
 
     fn matches_exact_str(&self, string: &str) -> Match {
         (**self).matches_exact_str(string)
     }
 
+    fn find_from<'a>(
+        &self,
+        chars: &'a [char],
+        start: usize,
+    ) -> Option<(MatchIndices, MatchState<'a>)> {
+        (**self).find_from(chars, start)
+    }
+
     fn matches(&self, string: &str) -> bool {
         (**self).matches(string)
     }
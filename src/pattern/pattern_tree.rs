@@ -0,0 +1,98 @@
+use std::fmt::{Display, Formatter};
+
+/// A readable description of a Pattern, printed by --debug-pattern
+#[derive(Debug, PartialEq, Clone)]
+pub enum PatternTree {
+    /// A pattern that matches without consuming any characters, such as the end of a chain of patterns
+    Empty,
+    /// Patterns that must match one after the other
+    Sequence(Vec<PatternTree>),
+    Node {
+        label: String,
+        children: Vec<PatternTree>,
+    },
+}
+
+impl PatternTree {
+    pub fn leaf(label: impl Into<String>) -> PatternTree {
+        PatternTree::node(label, vec![])
+    }
+
+    pub fn node(label: impl Into<String>, children: Vec<PatternTree>) -> PatternTree {
+        PatternTree::Node {
+            label: label.into(),
+            children,
+        }
+    }
+
+    /// The tree of `first` followed by `next`, flattening nested sequences so chains read as a list
+    pub fn followed_by(first: PatternTree, next: PatternTree) -> PatternTree {
+        let mut patterns = first.into_sequence();
+        patterns.extend(next.into_sequence());
+        match patterns.len() {
+            0 => PatternTree::Empty,
+            1 => patterns.remove(0),
+            _ => PatternTree::Sequence(patterns),
+        }
+    }
+
+    fn into_sequence(self) -> Vec<PatternTree> {
+        match self {
+            PatternTree::Empty => vec![],
+            PatternTree::Sequence(patterns) => patterns,
+            node => vec![node],
+        }
+    }
+
+    fn fmt_indented(&self, f: &mut Formatter<'_>, depth: usize) -> std::fmt::Result {
+        let indent = "  ".repeat(depth);
+        let (label, children) = match self {
+            PatternTree::Empty => ("Empty", &vec![]),
+            PatternTree::Sequence(patterns) => ("Sequence", patterns),
+            PatternTree::Node { label, children } => (label.as_str(), children),
+        };
+        writeln!(f, "{indent}{label}")?;
+        children
+            .iter()
+            .try_for_each(|child| child.fmt_indented(f, depth + 1))
+    }
+}
+
+impl Display for PatternTree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_followed_by_flattens_sequences() {
+        let tree = PatternTree::followed_by(
+            PatternTree::leaf("a"),
+            PatternTree::followed_by(PatternTree::leaf("b"), PatternTree::Empty),
+        );
+        assert_eq!(
+            tree,
+            PatternTree::Sequence(vec![PatternTree::leaf("a"), PatternTree::leaf("b")])
+        );
+        assert_eq!(
+            PatternTree::followed_by(PatternTree::leaf("a"), PatternTree::Empty),
+            PatternTree::leaf("a")
+        );
+    }
+
+    #[test]
+    fn test_display_indents_children() {
+        let tree = PatternTree::node(
+            "OneOrMore",
+            vec![PatternTree::Sequence(vec![
+                PatternTree::leaf("a"),
+                PatternTree::leaf("b"),
+            ])],
+        );
+        assert_eq!(tree.to_string(), "OneOrMore\n  Sequence\n    a\n    b\n");
+    }
+}
@@ -0,0 +1,394 @@
+use crate::pattern::match_state::{MatchState, RepetitionEnd};
+use crate::pattern::{always_match, ChainablePattern, Match, Pattern, PatternTree};
+
+/// Matches the inner pattern between `min` and `max` times (inclusive), as many times as possible,
+/// or as few times as possible if it is lazy
+/// Repetitions are tried in a loop rather than by recursing once per repetition, so long lines can't overflow
+/// the stack
+#[derive(Debug)]
+struct RepeatPattern {
+    min: usize,
+    /// There is no maximum if this is None
+    max: Option<usize>,
+    lazy: bool,
+    /// Shown in the tree instead of the bounds, for quantifiers like + that have their own name
+    name: Option<&'static str>,
+    repeated: RepeatedPattern,
+    /// The pattern to match after the last repetition
+    next_pattern: Box<dyn ChainablePattern>,
+    inner_tree: PatternTree,
+}
+
+/// How the inner pattern of a repeat is matched
+#[derive(Debug)]
+enum RepeatedPattern {
+    /// An inner pattern that matches in at most one way, so each repetition has one end
+    Looped(Box<dyn ChainablePattern>),
+    /// The inner pattern, followed by a RepetitionEndPattern that records each way the inner pattern can match,
+    /// e.g. each option of an alternation, so the repeat can backtrack into them when what follows doesn't match
+    Chained(Box<dyn ChainablePattern>),
+}
+
+/// The repetitions that have matched so far, for a repeat that is backtracking through them
+struct RepetitionFrame {
+    /// Where the repetition ends, relative to where the repeat started
+    end: usize,
+    /// The checkpoint to restore the groups to what the repetitions so far recorded
+    checkpoint: usize,
+    /// The ways the next repetition can end that are still to be tried, found when they are first needed
+    next_ends: Option<std::vec::IntoIter<RepetitionEnd>>,
+    /// Whether the next pattern has been tried after these repetitions
+    stopped: bool,
+}
+
+impl RepeatPattern {
+    /// Whether the inner pattern can match again, after it has already matched `count` times
+    fn can_repeat(&self, count: usize) -> bool {
+        self.max.is_none_or(|max| count < max)
+    }
+
+    /// Match the next pattern after the inner pattern has matched `count` times, ending at the start of `chars`
+    fn stop(&self, chars: &[char], state: &mut MatchState, count: usize) -> Match {
+        match count >= self.min {
+            true => state.attempt(|state| self.next_pattern.matches_exact(chars, state)),
+            false => Match::None,
+        }
+    }
+
+    /// Match an inner pattern that matches in at most one way, by finding where each repetition ends in a loop
+    /// and then trying the next pattern after each of them, most repetitions first unless the repeat is lazy
+    fn matches_looped(
+        &self,
+        inner_pattern: &dyn Pattern,
+        chars: &[char],
+        state: &mut MatchState,
+    ) -> Match {
+        // where the repetitions so far end, and the checkpoint to restore the groups to what they recorded
+        let mut ends = vec![(0, state.checkpoint())];
+        let mut repeating = true;
+        loop {
+            let count = ends.len() - 1;
+            let (end, checkpoint) = ends[count];
+            if self.lazy {
+                state.restore(checkpoint);
+                if let Match::Match(next_match) = self.stop(&chars[end..], state, count) {
+                    return Match::at(0, end + next_match.end);
+                }
+            }
+            if !repeating || !self.can_repeat(count) {
+                break;
+            }
+            match inner_pattern.matches_exact(&chars[end..], state) {
+                Match::Match(inner_match) => {
+                    // once `min` has been reached, a repetition that only matches an empty string still records
+                    // its groups, but it is the last one, to avoid looping forever
+                    repeating = inner_match.end > 0 || count < self.min;
+                    ends.push((end + inner_match.end, state.checkpoint()))
+                }
+                Match::None => break,
+            }
+        }
+        if !self.lazy {
+            for (count, &(end, checkpoint)) in ends.iter().enumerate().rev() {
+                state.restore(checkpoint);
+                if let Match::Match(next_match) = self.stop(&chars[end..], state, count) {
+                    return Match::at(0, end + next_match.end);
+                }
+            }
+        }
+        state.restore(ends[0].1);
+        Match::None
+    }
+
+    /// Match an inner pattern that can match in more than one way, by backtracking through the ways each
+    /// repetition can end with a stack of the repetitions so far
+    /// Tries more repetitions before fewer, and earlier ways of matching each repetition before later ones,
+    /// or fewer repetitions first if the repeat is lazy
+    fn matches_chained(
+        &self,
+        chained_pattern: &dyn Pattern,
+        chars: &[char],
+        state: &mut MatchState,
+    ) -> Match {
+        let start_checkpoint = state.checkpoint();
+        let mut frames = vec![RepetitionFrame {
+            end: 0,
+            checkpoint: start_checkpoint,
+            next_ends: None,
+            stopped: false,
+        }];
+        while !frames.is_empty() {
+            let count = frames.len() - 1;
+            let frame = &mut frames[count];
+            state.restore(frame.checkpoint);
+            if self.lazy && !frame.stopped {
+                frame.stopped = true;
+                if let Match::Match(next_match) = self.stop(&chars[frame.end..], state, count) {
+                    return Match::at(0, frame.end + next_match.end);
+                }
+                continue;
+            }
+            let end = frame.end;
+            let next_ends = frame
+                .next_ends
+                .get_or_insert_with(|| match self.can_repeat(count) {
+                    true => state
+                        .repetition_ends(|state| {
+                            chained_pattern.matches_exact(&chars[end..], state)
+                        })
+                        .into_iter(),
+                    false => Vec::new().into_iter(),
+                });
+            if let Some(repetition_end) = next_ends.next() {
+                let next_end = repetition_end.end - state.position(chars);
+                state.replay(&repetition_end);
+                frames.push(RepetitionFrame {
+                    end: next_end,
+                    checkpoint: state.checkpoint(),
+                    // once `min` has been reached, a repetition that only matches an empty string still records
+                    // its groups, but it is the last one, to avoid looping forever
+                    next_ends: match next_end == end && count >= self.min {
+                        true => Some(Vec::new().into_iter()),
+                        false => None,
+                    },
+                    stopped: false,
+                });
+                continue;
+            }
+            if !frame.stopped {
+                frame.stopped = true;
+                if let Match::Match(next_match) = self.stop(&chars[end..], state, count) {
+                    return Match::at(0, end + next_match.end);
+                }
+            }
+            frames.pop();
+        }
+        state.restore(start_checkpoint);
+        Match::None
+    }
+
+    fn describe(&self) -> String {
+        if let Some(name) = self.name {
+            return name.to_string();
+        }
+        let label = match self.lazy {
+            true => "LazyRepeat",
+            false => "Repeat",
+        };
+        match self.max {
+            Some(max) if max == self.min => format!("{label} {{{max}}}"),
+            Some(max) => format!("{label} {{{},{max}}}", self.min),
+            None => format!("{label} {{{},}}", self.min),
+        }
+    }
+}
+
+impl Pattern for RepeatPattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        match &self.repeated {
+            RepeatedPattern::Looped(inner_pattern) => {
+                self.matches_looped(inner_pattern, chars, state)
+            }
+            RepeatedPattern::Chained(chained_pattern) => {
+                self.matches_chained(chained_pattern, chars, state)
+            }
+        }
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::followed_by(
+            PatternTree::node(self.describe(), vec![self.inner_tree.clone()]),
+            self.next_pattern.tree(),
+        )
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        let (RepeatedPattern::Looped(inner_pattern) | RepeatedPattern::Chained(inner_pattern)) =
+            &self.repeated;
+        let repeated_width = match (inner_pattern.max_width()?, self.max) {
+            (0, _) => 0,
+            (inner_width, Some(max)) => inner_width.checked_mul(max)?,
+            (_, None) => return None,
+        };
+        repeated_width.checked_add(self.next_pattern.max_width()?)
+    }
+}
+
+impl ChainablePattern for RepeatPattern {
+    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        Box::new(RepeatPattern {
+            next_pattern: self.next_pattern.followed_by(pattern),
+            ..self
+        })
+    }
+}
+
+/// Matches at the end of each way the inner pattern of a repeat can match, recording where it ended and then
+/// failing so that the inner pattern goes on to try its other ways
+#[derive(Debug)]
+struct RepetitionEndPattern;
+
+impl Pattern for RepetitionEndPattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        state.record_repetition_end(chars);
+        Match::None
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::Empty
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        Some(0)
+    }
+}
+
+impl ChainablePattern for RepetitionEndPattern {
+    // The end is always the end of the inner pattern, since the next pattern is matched by the repeat
+    fn followed_by(self, _: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        unreachable!("patterns following a repeat are matched after its last repetition")
+    }
+}
+
+fn new_repeat(
+    pattern: Box<dyn ChainablePattern>,
+    min: usize,
+    max: Option<usize>,
+    lazy: bool,
+    name: Option<&'static str>,
+) -> RepeatPattern {
+    let inner_tree = pattern.tree();
+    RepeatPattern {
+        min,
+        max,
+        lazy,
+        name,
+        repeated: match pattern.matches_one_way() {
+            true => RepeatedPattern::Looped(pattern),
+            false => RepeatedPattern::Chained(pattern.followed_by(Box::new(RepetitionEndPattern))),
+        },
+        next_pattern: Box::new(always_match()),
+        inner_tree,
+    }
+}
+
+/// Repeat the pattern between `min` and `max` times, or at least `min` times if there is no `max`
+pub fn repeat(
+    pattern: Box<dyn ChainablePattern>,
+    min: usize,
+    max: Option<usize>,
+) -> impl ChainablePattern {
+    new_repeat(pattern, min, max, false, None)
+}
+
+/// Repeat the pattern between `min` and `max` times like `repeat`, but as few times as possible
+pub fn lazy_repeat(
+    pattern: Box<dyn ChainablePattern>,
+    min: usize,
+    max: Option<usize>,
+) -> impl ChainablePattern {
+    new_repeat(pattern, min, max, true, None)
+}
+
+/// Repeat the pattern like `repeat`, for a quantifier such as + that is shown by its name in the tree
+pub fn quantifier(
+    pattern: Box<dyn ChainablePattern>,
+    min: usize,
+    max: Option<usize>,
+    name: &'static str,
+) -> impl ChainablePattern {
+    new_repeat(pattern, min, max, false, Some(name))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::character_pattern::{literal, literal_str};
+    use crate::pattern::{alternating, one_or_more, wildcard, zero_or_one};
+
+    #[test]
+    fn test_exact_repeat() {
+        let pattern = repeat(Box::new(literal('a')), 3, Some(3));
+        assert_eq!(pattern.matches_exact_str("aa"), Match::None);
+        assert_eq!(pattern.matches_exact_str("aaa"), Match::at(0, 3));
+        assert_eq!(pattern.matches_exact_str("aaaa"), Match::at(0, 3));
+    }
+
+    #[test]
+    fn test_bounded_repeat() {
+        let pattern = repeat(literal_str("ab"), 1, Some(2));
+        assert_eq!(pattern.matches_exact_str("a"), Match::None);
+        assert_eq!(pattern.matches_exact_str("ab"), Match::at(0, 2));
+        assert_eq!(pattern.matches_exact_str("ababab"), Match::at(0, 4));
+
+        let pattern = repeat(Box::new(literal('a')), 0, Some(2));
+        assert_eq!(pattern.matches_exact_str("b"), Match::at(0, 0));
+        assert_eq!(pattern.matches_exact_str("aaa"), Match::at(0, 2));
+    }
+
+    #[test]
+    fn test_unbounded_repeat() {
+        let pattern = repeat(Box::new(literal('a')), 2, None);
+        assert_eq!(pattern.matches_exact_str("a"), Match::None);
+        assert_eq!(pattern.matches_exact_str("aaaaab"), Match::at(0, 5));
+    }
+
+    #[test]
+    fn test_repeat_followed_by_pattern() {
+        let pattern = repeat(Box::new(literal('a')), 2, Some(4)).followed_by(literal_str("ab"));
+        assert_eq!(pattern.matches_exact_str("aab"), Match::None);
+        assert_eq!(pattern.matches_exact_str("aaab"), Match::at(0, 4));
+        assert_eq!(pattern.matches_exact_str("aaaaab"), Match::at(0, 6));
+        assert_eq!(pattern.matches_exact_str("aaaaaab"), Match::None);
+    }
+
+    #[test]
+    fn test_lazy_repeat() {
+        let pattern = lazy_repeat(Box::new(literal('a')), 2, Some(4));
+        assert_eq!(pattern.matches_exact_str("a"), Match::None);
+        assert_eq!(pattern.matches_exact_str("aaaa"), Match::at(0, 2));
+
+        let pattern = lazy_repeat(Box::new(literal('a')), 0, None).followed_by(literal_str("ab"));
+        assert_eq!(pattern.matches_exact_str("ab"), Match::at(0, 2));
+        assert_eq!(pattern.matches_exact_str("aaab"), Match::at(0, 4));
+        assert_eq!(pattern.matches_exact_str("aaa"), Match::None);
+    }
+
+    #[test]
+    fn test_lazy_repeat_stops_at_first_match_of_next_pattern() {
+        let pattern = literal('<')
+            .followed_by(Box::new(lazy_repeat(Box::new(wildcard()), 1, None)))
+            .followed_by(Box::new(literal('>')));
+        assert_eq!(pattern.matches_exact_str("<a><b>"), Match::at(0, 3));
+        assert_eq!(pattern.matches_exact_str("<>>"), Match::at(0, 3));
+    }
+
+    #[test]
+    fn test_repeat_backtracks_into_inner_pattern() {
+        let options = alternating(vec![literal_str("a"), literal_str("ab")]);
+        let pattern = repeat(Box::new(options), 1, Some(2)).followed_by(literal_str("c"));
+        assert_eq!(pattern.matches_exact_str("abc"), Match::at(0, 3));
+        assert_eq!(pattern.matches_exact_str("aabc"), Match::at(0, 4));
+
+        let pattern = repeat(Box::new(one_or_more(Box::new(literal('a')))), 2, Some(2));
+        assert_eq!(pattern.matches_exact_str("aa"), Match::at(0, 2));
+        assert_eq!(pattern.matches_exact_str("a"), Match::None);
+    }
+
+    #[test]
+    fn test_looped_repeat_tries_each_number_of_repetitions() {
+        let pattern = repeat(Box::new(wildcard()), 1, None).followed_by(literal_str("ab"));
+        assert_eq!(pattern.matches_exact_str("xabyab"), Match::at(0, 6));
+        assert_eq!(pattern.matches_exact_str("abab"), Match::at(0, 4));
+        assert_eq!(pattern.matches_exact_str("ab"), Match::None);
+
+        let pattern = lazy_repeat(Box::new(wildcard()), 1, None).followed_by(literal_str("ab"));
+        assert_eq!(pattern.matches_exact_str("xabyab"), Match::at(0, 3));
+    }
+
+    #[test]
+    fn test_repeat_of_empty_match_terminates() {
+        let pattern = repeat(Box::new(zero_or_one(Box::new(literal('a')))), 3, None);
+        assert_eq!(pattern.matches_exact_str("a"), Match::at(0, 1));
+        assert_eq!(pattern.matches_exact_str("aaaaa"), Match::at(0, 5));
+    }
+}
@@ -1,38 +1,62 @@
-use crate::pattern::{Match, Pattern};
+use crate::pattern::match_state::MatchState;
+use crate::pattern::union_pattern::union;
+use crate::pattern::{ChainablePattern, Match, Pattern, PatternTree};
 
+/// Matches an empty string at the start of the line
 #[derive(Debug)]
-struct StartLineAnchor {
-    inner_pattern: Box<dyn Pattern>,
-}
+struct StartLineAnchor {}
 
 impl Pattern for StartLineAnchor {
-    fn matches_exact(&self, _chars: &[char]) -> Match {
-        panic!("Start line pattern shouldn't be matched after any other pattern, it must always be the first pattern")
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        match state.position(chars) {
+            0 => Match::at(0, 0),
+            _ => Match::None,
+        }
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::leaf("StartLineAnchor")
+    }
+
+    fn matches_one_way(&self) -> bool {
+        true
     }
 
-    // ensure the pattern only matches from the start of the string
-    // by overriding matches to only check from the start of the string
-    fn matches(&self, string: &str) -> bool {
-        self.inner_pattern.matches_exact_str(string).is_match()
+    fn max_width(&self) -> Option<usize> {
+        Some(0)
     }
 }
 
-pub fn start_line_anchor(pattern: Box<dyn Pattern>) -> impl Pattern {
-    StartLineAnchor {
-        inner_pattern: pattern,
+impl ChainablePattern for StartLineAnchor {
+    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        union(self, pattern)
     }
 }
 
+pub fn start_line_anchor() -> impl ChainablePattern {
+    StartLineAnchor {}
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pattern::literal;
+    use crate::pattern::character_pattern::literal_str;
+    use crate::pattern::{alternating, literal};
 
     #[test]
     fn test_start_line_anchor() {
-        let pattern = start_line_anchor(Box::new(literal('a')));
+        let pattern = start_line_anchor().followed_by(Box::new(literal('a')));
         assert!(pattern.matches("abcd"));
         assert!(!pattern.matches(" abcd"));
         assert!(!pattern.matches("baaaaa"));
     }
+
+    #[test]
+    fn test_start_line_anchor_in_alternation() {
+        let start_x = start_line_anchor().followed_by(literal_str("x"));
+        let pattern = alternating(vec![start_x, literal_str("y")]);
+        assert!(pattern.matches("xa"));
+        assert!(pattern.matches("ay"));
+        assert!(!pattern.matches("ax"));
+    }
 }
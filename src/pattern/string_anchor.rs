@@ -0,0 +1,96 @@
+use crate::pattern::match_state::MatchState;
+use crate::pattern::union_pattern::union;
+use crate::pattern::{ChainablePattern, Match, Pattern, PatternTree};
+
+#[derive(Debug)]
+enum Anchor {
+    Start,
+    End,
+    /// The end of the string, or just before a newline that ends the string
+    EndBeforeNewline,
+}
+
+/// Matches an empty string at the start or end of the whole string being matched, whatever it contains
+#[derive(Debug)]
+struct StringAnchor {
+    anchor: Anchor,
+}
+
+impl Pattern for StringAnchor {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        let matches = match self.anchor {
+            Anchor::Start => state.position(chars) == 0,
+            Anchor::End => chars.is_empty(),
+            Anchor::EndBeforeNewline => matches!(chars, [] | ['\n']),
+        };
+        match matches {
+            true => Match::at(0, 0),
+            false => Match::None,
+        }
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::leaf(match self.anchor {
+            Anchor::Start => "StringStart",
+            Anchor::End => "StringEnd",
+            Anchor::EndBeforeNewline => "StringEndBeforeNewline",
+        })
+    }
+
+    fn matches_one_way(&self) -> bool {
+        true
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        Some(0)
+    }
+}
+
+impl ChainablePattern for StringAnchor {
+    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        union(self, pattern)
+    }
+}
+
+/// Match at the start of the string, like \A
+pub fn string_start() -> impl ChainablePattern {
+    StringAnchor {
+        anchor: Anchor::Start,
+    }
+}
+
+/// Match at the very end of the string, like \z
+pub fn string_end() -> impl ChainablePattern {
+    StringAnchor {
+        anchor: Anchor::End,
+    }
+}
+
+/// Match at the end of the string or before a final newline, like \Z
+pub fn string_end_before_newline() -> impl ChainablePattern {
+    StringAnchor {
+        anchor: Anchor::EndBeforeNewline,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::character_pattern::literal_str;
+
+    #[test]
+    fn test_string_anchors() {
+        let pattern = string_start().followed_by(literal_str("a"));
+        assert!(pattern.matches("ab"));
+        assert!(!pattern.matches("ba"));
+
+        let pattern = literal_str("b").followed_by(Box::new(string_end()));
+        assert!(pattern.matches("ab"));
+        assert!(!pattern.matches("ab\n"));
+
+        let pattern = literal_str("b").followed_by(Box::new(string_end_before_newline()));
+        assert!(pattern.matches("ab"));
+        assert!(pattern.matches("ab\n"));
+        assert!(!pattern.matches("ab\n\n"));
+    }
+}
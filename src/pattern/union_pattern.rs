@@ -1,11 +1,12 @@
+use crate::pattern::match_state::MatchState;
 use crate::pattern::match_struct::combine_match;
 use crate::pattern::ChainablePattern;
-use crate::pattern::{Match, Pattern};
+use crate::pattern::{Match, Pattern, PatternTree};
 
 #[derive(Debug)]
 struct UnionPattern {
     first: Box<dyn Pattern>,
-    second: Box<dyn Pattern>,
+    second: Box<dyn ChainablePattern>,
 }
 
 pub fn union<T: ChainablePattern + Sized + 'static>(
@@ -16,18 +17,35 @@ pub fn union<T: ChainablePattern + Sized + 'static>(
 }
 
 impl Pattern for UnionPattern {
-    fn matches_exact(&self, chars: &[char]) -> Match {
-        self.first.matches_exact(chars).and_then(|first_match| {
-            self.second
-                .matches_exact(&chars[first_match.end..])
-                .and_then(|second_match| combine_match(first_match, second_match))
-        })
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        self.first
+            .matches_exact(chars, state)
+            .and_then(|first_match| {
+                self.second
+                    .matches_exact(&chars[first_match.end..], state)
+                    .and_then(|second_match| combine_match(first_match, second_match))
+            })
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::followed_by(self.first.tree(), self.second.tree())
+    }
+
+    fn matches_one_way(&self) -> bool {
+        self.first.matches_one_way() && self.second.matches_one_way()
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        self.first.max_width()?.checked_add(self.second.max_width()?)
     }
 }
 
 impl ChainablePattern for UnionPattern {
     fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
-        union(self, pattern)
+        Box::new(UnionPattern {
+            first: self.first,
+            second: self.second.followed_by(pattern),
+        })
     }
 }
 
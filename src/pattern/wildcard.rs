@@ -1,5 +1,6 @@
-use crate::pattern::{ChainablePattern, Match, Pattern};
+use crate::pattern::match_state::MatchState;
 use crate::pattern::union_pattern::union;
+use crate::pattern::{ChainablePattern, Match, Pattern, PatternTree};
 
 #[derive(Debug)]
 struct WildcardPattern {}
@@ -9,13 +10,25 @@ pub fn wildcard() -> impl ChainablePattern {
 }
 
 impl Pattern for WildcardPattern {
-    fn matches_exact(&self, chars: &[char]) -> Match {
-        if chars.is_empty() {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        if state.matchable(chars).is_empty() {
             Match::None
         } else {
             Match::at(0, 1)
         }
     }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::leaf("Wildcard")
+    }
+
+    fn matches_one_way(&self) -> bool {
+        true
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        Some(1)
+    }
 }
 
 impl ChainablePattern for WildcardPattern {
@@ -0,0 +1,134 @@
+use crate::character_class;
+use crate::character_class::CharacterClass;
+use crate::pattern::match_state::MatchState;
+use crate::pattern::union_pattern::union;
+use crate::pattern::{ChainablePattern, Match, Pattern, PatternTree};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Boundary {
+    /// Between a word character and a non word character, in either order
+    Word,
+    /// Between two word characters or two non word characters
+    NotWord,
+    /// Before a word character that doesn't follow another word character
+    WordStart,
+    /// After a word character that isn't followed by another word character
+    WordEnd,
+}
+
+/// Matches an empty string at a boundary between word characters, which are the characters matched by \w
+/// The start and end of the line count as non word characters
+#[derive(Debug)]
+struct WordBoundaryPattern {
+    boundary: Boundary,
+    word_chars: Box<dyn CharacterClass>,
+}
+
+impl Pattern for WordBoundaryPattern {
+    fn matches_exact(&self, chars: &[char], state: &mut MatchState) -> Match {
+        let is_word = |char: Option<&char>| char.is_some_and(|char| self.word_chars.matches(*char));
+        let position = state.position(chars);
+        let word_before = is_word(position.checked_sub(1).map(|idx| &state.input()[idx]));
+        let word_after = is_word(chars.first());
+        let at_boundary = match self.boundary {
+            Boundary::Word => word_before != word_after,
+            Boundary::NotWord => word_before == word_after,
+            Boundary::WordStart => !word_before && word_after,
+            Boundary::WordEnd => word_before && !word_after,
+        };
+        match at_boundary {
+            true => Match::at(0, 0),
+            false => Match::None,
+        }
+    }
+
+    fn tree(&self) -> PatternTree {
+        PatternTree::leaf(match self.boundary {
+            Boundary::Word => "WordBoundary",
+            Boundary::NotWord => "NotWordBoundary",
+            Boundary::WordStart => "WordStart",
+            Boundary::WordEnd => "WordEnd",
+        })
+    }
+
+    fn matches_one_way(&self) -> bool {
+        true
+    }
+
+    fn max_width(&self) -> Option<usize> {
+        Some(0)
+    }
+}
+
+impl ChainablePattern for WordBoundaryPattern {
+    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
+        union(self, pattern)
+    }
+}
+
+fn boundary(boundary: Boundary) -> impl ChainablePattern {
+    WordBoundaryPattern {
+        boundary,
+        word_chars: Box::new(character_class::alphanumeric()),
+    }
+}
+
+/// Match at the start or end of a word, like \b
+pub fn word_boundary() -> impl ChainablePattern {
+    boundary(Boundary::Word)
+}
+
+/// Match anywhere that isn't the start or end of a word, like \B
+pub fn not_word_boundary() -> impl ChainablePattern {
+    boundary(Boundary::NotWord)
+}
+
+/// Match at the start of a word, like \<
+pub fn word_start() -> impl ChainablePattern {
+    boundary(Boundary::WordStart)
+}
+
+/// Match at the end of a word, like \>
+pub fn word_end() -> impl ChainablePattern {
+    boundary(Boundary::WordEnd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::character_pattern::literal_str;
+
+    fn match_starts(pattern: &impl Pattern, string: &str) -> Vec<usize> {
+        let chars: Box<[char]> = string.chars().collect();
+        (0..=chars.len())
+            .filter(|&start| {
+                pattern
+                    .matches_exact(&chars[start..], &mut MatchState::new(&chars))
+                    .is_match()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_boundaries() {
+        assert_eq!(match_starts(&word_boundary(), "ab c_1."), vec![0, 2, 3, 6]);
+        assert_eq!(
+            match_starts(&not_word_boundary(), "ab c_1."),
+            vec![1, 4, 5, 7]
+        );
+        assert_eq!(match_starts(&word_start(), "ab c_1."), vec![0, 3]);
+        assert_eq!(match_starts(&word_end(), "ab c_1."), vec![2, 6]);
+        assert_eq!(match_starts(&word_boundary(), ""), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_whole_word() {
+        let pattern = word_boundary()
+            .followed_by(literal_str("cat"))
+            .followed_by(Box::new(word_boundary()));
+        assert!(pattern.matches("the cat sat"));
+        assert!(pattern.matches("cat"));
+        assert!(!pattern.matches("concatenate"));
+        assert!(!pattern.matches("cats"));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::pattern::repeat::quantifier;
+use crate::pattern::ChainablePattern;
+
+/// Match the pattern as many times as possible, including not at all
+pub fn zero_or_more(pattern: Box<dyn ChainablePattern>) -> impl ChainablePattern {
+    quantifier(pattern, 0, None, "ZeroOrMore")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::character_pattern::{literal, literal_str};
+    use crate::pattern::{alternating, zero_or_one};
+    use crate::pattern::{Match, Pattern};
+
+    #[test]
+    fn test_simple_repeat() {
+        let pattern = zero_or_more(Box::new(literal('a')));
+        assert_eq!(pattern.matches_exact_str(""), Match::at(0, 0));
+        assert_eq!(pattern.matches_exact_str("b"), Match::at(0, 0));
+        assert_eq!(pattern.matches_exact_str("a"), Match::at(0, 1));
+        assert_eq!(pattern.matches_exact_str("aaab"), Match::at(0, 3));
+    }
+
+    #[test]
+    fn test_complex_repeat() {
+        let pattern = zero_or_more(literal_str("ab"));
+        assert_eq!(pattern.matches_exact_str("a"), Match::at(0, 0));
+        assert_eq!(pattern.matches_exact_str("abab"), Match::at(0, 4));
+        assert_eq!(pattern.matches_exact_str("ababa"), Match::at(0, 4));
+    }
+
+    #[test]
+    fn test_repeat_followed_by_pattern() {
+        let pattern = zero_or_more(Box::new(literal('a'))).followed_by(literal_str("ab"));
+        assert_eq!(pattern.matches_exact_str("a"), Match::None);
+        assert_eq!(pattern.matches_exact_str("ab"), Match::at(0, 2));
+        assert_eq!(pattern.matches_exact_str("aab"), Match::at(0, 3));
+        assert_eq!(pattern.matches_exact_str("aaab"), Match::at(0, 4));
+        assert_eq!(pattern.matches_exact_str("aaac"), Match::None);
+    }
+
+    #[test]
+    fn test_repeat_of_empty_match_terminates() {
+        let pattern = zero_or_more(Box::new(zero_or_one(Box::new(literal('a')))));
+        assert_eq!(pattern.matches_exact_str("aab"), Match::at(0, 2));
+        assert_eq!(pattern.matches_exact_str("b"), Match::at(0, 0));
+
+        let pattern = zero_or_more(Box::new(zero_or_one(Box::new(literal('a')))))
+            .followed_by(literal_str("b"));
+        assert_eq!(pattern.matches_exact_str("aab"), Match::at(0, 3));
+        assert_eq!(pattern.matches_exact_str("c"), Match::None);
+    }
+
+    #[test]
+    fn test_repeat_backtracks_into_inner_pattern() {
+        let options = alternating(vec![literal_str("ab"), literal_str("a")]);
+        let pattern = zero_or_more(Box::new(options)).followed_by(literal_str("b"));
+        assert_eq!(pattern.matches_exact_str("abab"), Match::at(0, 4));
+        assert_eq!(pattern.matches_exact_str("b"), Match::at(0, 1));
+    }
+
+    #[test]
+    fn test_long_repeat_of_alternation() {
+        let line = "ab".repeat(50_000);
+        let options = alternating(vec![literal_str("a"), literal_str("b")]);
+        let pattern = zero_or_more(Box::new(options)).followed_by(literal_str("c"));
+        assert_eq!(
+            pattern.matches_exact_str(&format!("{line}c")),
+            Match::at(0, line.len() + 1)
+        );
+        assert_eq!(pattern.matches_exact_str("abab"), Match::None);
+    }
+}
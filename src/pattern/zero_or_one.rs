@@ -1,68 +1,17 @@
-use crate::pattern::match_struct::combine_match;
-use crate::pattern::{ChainablePattern, Match, Pattern};
-use crate::pattern::union_pattern::union;
-
-#[derive(Debug)]
-struct ZeroOrOnePattern {
-    inner_pattern: Box<dyn Pattern>,
-}
-
-impl Pattern for ZeroOrOnePattern {
-    fn matches_exact(&self, chars: &[char]) -> Match {
-        self.inner_pattern
-            .matches_exact(chars)
-            .match_or(Match::at(0, 0)) // match inner pattern, or match zero times
-    }
-}
-
-impl ChainablePattern for ZeroOrOnePattern {
-    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern>
-    where
-        Self: Sized + 'static,
-    {
-        Box::new(ZeroOrOneFollowedByPattern {
-            zero_or_one: self,
-            next_pattern: pattern,
-        })
-    }
-}
-
-#[derive(Debug)]
-struct ZeroOrOneFollowedByPattern {
-    zero_or_one: ZeroOrOnePattern,
-    next_pattern: Box<dyn Pattern>,
-}
-
-impl Pattern for ZeroOrOneFollowedByPattern {
-    fn matches_exact(&self, chars: &[char]) -> Match {
-        match self.zero_or_one.inner_pattern.matches_exact(chars) {
-            Match::None => self.next_pattern.matches_exact(chars),
-            Match::Match(first_match) => {
-                match self.next_pattern.matches_exact(&chars[first_match.end..]) {
-                    Match::None => self.next_pattern.matches_exact(chars),
-                    Match::Match(second_match) => combine_match(&first_match, &second_match),
-                }
-            }
-        }
-    }
-}
-
-impl ChainablePattern for ZeroOrOneFollowedByPattern {
-    fn followed_by(self, pattern: Box<dyn ChainablePattern>) -> Box<dyn ChainablePattern> {
-        union(self, pattern)
-    }
-}
+use crate::pattern::repeat::quantifier;
+use crate::pattern::ChainablePattern;
 
+/// Match the pattern once if possible, otherwise match an empty string
 pub fn zero_or_one(pattern: Box<dyn ChainablePattern>) -> impl ChainablePattern {
-    ZeroOrOnePattern {
-        inner_pattern: pattern,
-    }
+    quantifier(pattern, 0, Some(1), "ZeroOrOne")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pattern::alternating;
     use crate::pattern::character_pattern::{literal, literal_str};
+    use crate::pattern::{Match, Pattern};
 
     #[test]
     fn test_isolated_pattern() {
@@ -104,4 +53,12 @@ mod tests {
         assert_eq!(pattern.matches_exact_str("abbc"), Match::at(0, 4));
         assert_eq!(pattern.matches_exact_str("abbbc"), Match::at(0, 5));
     }
+
+    #[test]
+    fn test_backtracks_into_inner_pattern() {
+        let options = alternating(vec![literal_str("a"), literal_str("ab")]);
+        let pattern = zero_or_one(Box::new(options)).followed_by(literal_str("c"));
+        assert_eq!(pattern.matches_exact_str("abc"), Match::at(0, 3));
+        assert_eq!(pattern.matches_exact_str("c"), Match::at(0, 1));
+    }
 }
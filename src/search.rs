@@ -0,0 +1,561 @@
+use crate::matching::CompiledPattern;
+use crate::pattern::{Captures, Pattern};
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// How stdin is named in output and errors, it can also be searched alongside files with the path "-"
+const STDIN_NAME: &str = "(standard input)";
+
+/// The result of searching one or more inputs, used to decide the exit code
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct SearchOutcome {
+    /// Whether any line matched the pattern
+    pub matched: bool,
+    /// Whether any input could not be read
+    pub had_error: bool,
+}
+
+impl SearchOutcome {
+    fn combine(self, other: SearchOutcome) -> SearchOutcome {
+        SearchOutcome {
+            matched: self.matched || other.matched,
+            had_error: self.had_error || other.had_error,
+        }
+    }
+
+    fn error() -> SearchOutcome {
+        SearchOutcome {
+            matched: false,
+            had_error: true,
+        }
+    }
+
+    /// grep's exit codes: 0 if a line matched, 1 if no lines matched, and 2 if an error occurred
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            SearchOutcome {
+                had_error: true, ..
+            } => 2,
+            SearchOutcome { matched: true, .. } => 0,
+            SearchOutcome { matched: false, .. } => 1,
+        }
+    }
+}
+
+/// How lines are matched, and what is printed for each matching line
+pub struct LineMatcher<'a> {
+    pub pattern: &'a CompiledPattern,
+    /// Print the text captured by this group for each match in the line, instead of the whole line
+    /// Group 0 prints the whole of each match
+    pub only_group: Option<usize>,
+    /// Replace each match with this template, expanded by Captures::expand
+    pub replacement: Option<&'a str>,
+    /// Whether lines ending in \r\n are matched without the \r
+    pub crlf: bool,
+    /// Whether printed lines are prefixed with their line number, after the path when one is printed
+    pub line_number: bool,
+}
+
+impl LineMatcher<'_> {
+    /// Whether the line matches, writing what should be printed for it to `output`
+    /// `number` is the line's position in its input, counting from 1
+    fn match_line(
+        &self,
+        line_bytes: &[u8],
+        prefix: Option<&str>,
+        number: usize,
+        output: &mut impl Write,
+    ) -> io::Result<bool> {
+        // the terminator isn't matched, so that $ matches at the end of the line's text
+        let (text_bytes, terminator) = split_line_terminator(line_bytes, self.crlf);
+        // the last line of the input may not be terminated, but it's still printed as a whole line
+        let terminator = match terminator {
+            [] => b"\n",
+            terminator => terminator,
+        };
+        // don't fail the whole search because one line isn't valid utf-8
+        let line = String::from_utf8_lossy(text_bytes);
+        if self.only_group.is_none() && self.replacement.is_none() {
+            if !self.pattern.matches(&line) {
+                return Ok(false);
+            }
+            self.write_prefix(prefix, number, output)?;
+            output.write_all(text_bytes)?;
+            output.write_all(terminator)?;
+            return Ok(true);
+        }
+        let all_captures = self.pattern.captures_iter(&line);
+        let Some(group) = self.only_group else {
+            if all_captures.is_empty() {
+                return Ok(false);
+            }
+            self.write_prefix(prefix, number, output)?;
+            let template = self.replacement.unwrap_or_default();
+            write!(
+                output,
+                "{}",
+                replace_matches(&line, &all_captures, template)
+            )?;
+            output.write_all(terminator)?;
+            return Ok(true);
+        };
+        for captures in &all_captures {
+            let text = match self.replacement {
+                Some(template) => Some(captures.expand(template)).filter(|text| !text.is_empty()),
+                // like grep, empty matches aren't printed, but a group that took part in the match is printed
+                // even when it's empty
+                None => captures
+                    .get(group)
+                    .map(|capture| capture.text.clone())
+                    .filter(|text| group > 0 || !text.is_empty()),
+            };
+            if let Some(text) = text {
+                self.write_prefix(prefix, number, output)?;
+                writeln!(output, "{text}")?;
+            }
+        }
+        Ok(!all_captures.is_empty())
+    }
+
+    fn write_prefix(
+        &self,
+        prefix: Option<&str>,
+        number: usize,
+        output: &mut impl Write,
+    ) -> io::Result<()> {
+        if let Some(prefix) = prefix {
+            write!(output, "{prefix}:")?;
+        }
+        if self.line_number {
+            write!(output, "{number}:")?;
+        }
+        Ok(())
+    }
+}
+
+/// Replace each match in the line with the expanded template
+fn replace_matches(line: &str, all_captures: &[Captures], template: &str) -> String {
+    let chars: Box<[char]> = line.chars().collect();
+    let mut replaced = String::new();
+    let mut end = 0;
+    for captures in all_captures {
+        if let Some(whole_match) = captures.get(0) {
+            replaced.extend(&chars[end..whole_match.start]);
+            replaced.push_str(&captures.expand(template));
+            end = whole_match.end;
+        }
+    }
+    replaced.extend(&chars[end..]);
+    replaced
+}
+
+/// Split the line into its text and its terminator, which is \n, or \r\n if `crlf` is set
+/// The terminator is empty if the line isn't terminated
+fn split_line_terminator(line_bytes: &[u8], crlf: bool) -> (&[u8], &[u8]) {
+    let terminator_len = match line_bytes {
+        [.., b'\r', b'\n'] if crlf => 2,
+        [.., b'\n'] => 1,
+        _ => 0,
+    };
+    line_bytes.split_at(line_bytes.len() - terminator_len)
+}
+
+/// Match every line read from `reader`, writing the matching lines to `output`
+/// Each line is prefixed with `prefix:` when a prefix is given, followed by its line number if the matcher asks for one
+/// Returns whether any line matched
+pub fn search_lines(
+    mut reader: impl BufRead,
+    matcher: &LineMatcher,
+    prefix: Option<&str>,
+    output: &mut impl Write,
+) -> io::Result<bool> {
+    let mut any_matched = false;
+    let mut line_bytes = Vec::new();
+    for number in 1.. {
+        line_bytes.clear();
+        if reader.read_until(b'\n', &mut line_bytes)? == 0 {
+            break;
+        }
+        any_matched |= matcher.match_line(&line_bytes, prefix, number, output)?;
+    }
+    Ok(any_matched)
+}
+
+/// Search the lines piped to stdin
+pub fn search_stdin(matcher: &LineMatcher, output: &mut impl Write) -> SearchOutcome {
+    search_reader(io::stdin().lock(), STDIN_NAME, None, matcher, output)
+}
+
+/// Search a single input, reporting any errors reading it to stderr
+fn search_reader(
+    reader: impl BufRead,
+    name: &str,
+    prefix: Option<&str>,
+    matcher: &LineMatcher,
+    output: &mut impl Write,
+) -> SearchOutcome {
+    match search_lines(reader, matcher, prefix, output) {
+        Ok(matched) => SearchOutcome {
+            matched,
+            had_error: false,
+        },
+        Err(error) => {
+            eprintln!("grep: {name}: {error}");
+            SearchOutcome::error()
+        }
+    }
+}
+
+/// Search every file in `paths`, prefixing matching lines with their path when more than one file can be searched,
+/// which is when there are several paths or a directory is searched recursively
+/// Directories are only searched when `recursive` is set
+/// Files that can't be read are reported to stderr without stopping the rest of the search
+pub fn search_paths(
+    paths: &[PathBuf],
+    recursive: bool,
+    matcher: &LineMatcher,
+    output: &mut impl Write,
+) -> SearchOutcome {
+    let with_filename = paths.len() > 1 || (recursive && paths.iter().any(|path| path.is_dir()));
+    paths
+        .iter()
+        .fold(SearchOutcome::default(), |outcome, path| {
+            outcome.combine(search_path(path, recursive, with_filename, matcher, output))
+        })
+}
+
+fn search_path(
+    path: &Path,
+    recursive: bool,
+    with_filename: bool,
+    matcher: &LineMatcher,
+    output: &mut impl Write,
+) -> SearchOutcome {
+    if path == Path::new("-") {
+        let prefix = with_filename.then_some(STDIN_NAME);
+        return search_reader(io::stdin().lock(), STDIN_NAME, prefix, matcher, output);
+    }
+    if !path.is_dir() {
+        let name = path.display().to_string();
+        let prefix = with_filename.then_some(name.as_str());
+        return match File::open(path) {
+            Ok(file) => search_reader(BufReader::new(file), &name, prefix, matcher, output),
+            Err(error) => {
+                eprintln!("grep: {name}: {error}");
+                SearchOutcome::error()
+            }
+        };
+    }
+    if !recursive {
+        eprintln!("grep: {}: Is a directory", path.display());
+        return SearchOutcome::error();
+    }
+    match directory_entries(path) {
+        Ok(entries) => entries
+            .iter()
+            .fold(SearchOutcome::default(), |outcome, entry| {
+                outcome.combine(search_path(
+                    entry,
+                    recursive,
+                    with_filename,
+                    matcher,
+                    output,
+                ))
+            }),
+        Err(error) => {
+            eprintln!("grep: {}: {error}", path.display());
+            SearchOutcome::error()
+        }
+    }
+}
+
+/// List the entries of a directory in a stable order, skipping symlinks so that recursion can't loop forever
+fn directory_entries(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_symlink() {
+            entries.push(entry.path());
+        }
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::compile_pattern;
+    use std::env;
+
+    fn search_str(input: &str, matcher: &LineMatcher) -> (bool, String) {
+        let mut output = Vec::new();
+        let matched = search_lines(input.as_bytes(), matcher, None, &mut output).unwrap();
+        (matched, String::from_utf8(output).unwrap())
+    }
+
+    fn print_lines(pattern: &CompiledPattern) -> LineMatcher<'_> {
+        LineMatcher {
+            pattern,
+            only_group: None,
+            replacement: None,
+            crlf: false,
+            line_number: false,
+        }
+    }
+
+    /// Create a fresh directory containing the given files
+    fn create_test_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = env::temp_dir().join(format!("rust_grep_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for (file_name, contents) in files {
+            let file_path = dir.join(file_name);
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            fs::write(file_path, contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_prints_every_matching_line() {
+        let pattern = compile_pattern("dog").unwrap();
+        let (matched, output) = search_str("a dog\na cat\ndogs\n", &print_lines(&pattern));
+        assert!(matched);
+        assert_eq!(output, "a dog\ndogs\n");
+    }
+
+    #[test]
+    fn test_no_matching_lines() {
+        let pattern = compile_pattern("bird").unwrap();
+        let (matched, output) = search_str("a dog\na cat\n", &print_lines(&pattern));
+        assert!(!matched);
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn test_unterminated_last_line() {
+        let pattern = compile_pattern("cat").unwrap();
+        let (matched, output) = search_str("a dog\na cat", &print_lines(&pattern));
+        assert!(matched);
+        assert_eq!(output, "a cat\n");
+    }
+
+    #[test]
+    fn test_end_anchor_matches_before_terminator() {
+        let pattern = compile_pattern("cat$").unwrap();
+        let (matched, output) = search_str("a cat\na cat!\ncat", &print_lines(&pattern));
+        assert!(matched);
+        assert_eq!(output, "a cat\ncat\n");
+    }
+
+    #[test]
+    fn test_crlf_terminators() {
+        let pattern = compile_pattern("cat$").unwrap();
+        let (matched, _) = search_str("a cat\r\n", &print_lines(&pattern));
+        assert!(!matched);
+
+        let matcher = LineMatcher {
+            crlf: true,
+            ..print_lines(&pattern)
+        };
+        let (matched, output) = search_str("a cat\r\na dog\r\ncat\n", &matcher);
+        assert!(matched);
+        assert_eq!(output, "a cat\r\ncat\n");
+
+        let pattern = compile_pattern("(\\w+)$").unwrap();
+        let matcher = LineMatcher {
+            replacement: Some("<$1>"),
+            crlf: true,
+            ..print_lines(&pattern)
+        };
+        let (_, output) = search_str("a cat\r\n", &matcher);
+        assert_eq!(output, "a <cat>\r\n");
+    }
+
+    #[test]
+    fn test_search_multiple_files() {
+        let dir = create_test_dir(
+            "multiple",
+            &[("a.txt", "dog\ncat\n"), ("b.txt", "hotdog\n")],
+        );
+        let pattern = compile_pattern("dog").unwrap();
+        let mut output = Vec::new();
+        let outcome = search_paths(
+            &[
+                dir.join("a.txt"),
+                dir.join("missing.txt"),
+                dir.join("b.txt"),
+            ],
+            false,
+            &print_lines(&pattern),
+            &mut output,
+        );
+        assert_eq!(
+            outcome,
+            SearchOutcome {
+                matched: true,
+                had_error: true
+            }
+        );
+        let expected = format!(
+            "{}:dog\n{}:hotdog\n",
+            dir.join("a.txt").display(),
+            dir.join("b.txt").display()
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_search_directory_recursively() {
+        let dir = create_test_dir(
+            "recursive",
+            &[("a.txt", "dog\n"), ("nested/b.txt", "dogs\n")],
+        );
+        let pattern = compile_pattern("dog").unwrap();
+
+        let mut output = Vec::new();
+        let outcome = search_paths(
+            std::slice::from_ref(&dir),
+            false,
+            &print_lines(&pattern),
+            &mut output,
+        );
+        assert_eq!(outcome, SearchOutcome::error());
+
+        let mut output = Vec::new();
+        let outcome = search_paths(
+            std::slice::from_ref(&dir),
+            true,
+            &print_lines(&pattern),
+            &mut output,
+        );
+        assert_eq!(
+            outcome,
+            SearchOutcome {
+                matched: true,
+                had_error: false
+            }
+        );
+        let expected = format!(
+            "{}:dog\n{}:dogs\n",
+            dir.join("a.txt").display(),
+            dir.join("nested/b.txt").display()
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+        // a single file isn't prefixed with its path, even when searching recursively
+        let mut output = Vec::new();
+        let outcome = search_paths(
+            &[dir.join("a.txt")],
+            true,
+            &print_lines(&pattern),
+            &mut output,
+        );
+        assert!(outcome.matched);
+        assert_eq!(String::from_utf8(output).unwrap(), "dog\n");
+    }
+
+    #[test]
+    fn test_line_numbers() {
+        let pattern = compile_pattern("dog").unwrap();
+        let matcher = LineMatcher {
+            line_number: true,
+            ..print_lines(&pattern)
+        };
+        let (matched, output) = search_str("a dog\na cat\ndogs\n", &matcher);
+        assert!(matched);
+        assert_eq!(output, "1:a dog\n3:dogs\n");
+
+        // the line number follows the path, and each match printed from a line is numbered
+        let pattern = compile_pattern("\\d").unwrap();
+        let matcher = LineMatcher {
+            only_group: Some(0),
+            line_number: true,
+            ..print_lines(&pattern)
+        };
+        let mut output = Vec::new();
+        search_lines("x\n1 2\n".as_bytes(), &matcher, Some("a.txt"), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "a.txt:2:1\na.txt:2:2\n");
+    }
+
+    #[test]
+    fn test_only_group() {
+        let pattern = compile_pattern("(\\w+)=(\\d+)?").unwrap();
+        let matcher = LineMatcher {
+            pattern: &pattern,
+            only_group: Some(1),
+            replacement: None,
+            crlf: false,
+            line_number: false,
+        };
+        let (matched, output) = search_str("a=1 b=2\nnone\nc=\n", &matcher);
+        assert!(matched);
+        assert_eq!(output, "a\nb\nc\n");
+
+        // lines can match without printing anything, when the group doesn't take part in the match
+        let matcher = LineMatcher {
+            pattern: &pattern,
+            only_group: Some(2),
+            replacement: None,
+            crlf: false,
+            line_number: false,
+        };
+        let (matched, output) = search_str("a=1 b=2\nc=\n", &matcher);
+        assert!(matched);
+        assert_eq!(output, "1\n2\n");
+
+        // an empty group is printed when it took part in the match
+        let pattern = compile_pattern("(b?)?a").unwrap();
+        let matcher = LineMatcher {
+            only_group: Some(1),
+            ..print_lines(&pattern)
+        };
+        let (matched, output) = search_str("a\nba\n", &matcher);
+        assert!(matched);
+        assert_eq!(output, "\nb\n");
+    }
+
+    #[test]
+    fn test_only_matching() {
+        let pattern = compile_pattern("\\d+").unwrap();
+        let matcher = LineMatcher {
+            pattern: &pattern,
+            only_group: Some(0),
+            replacement: None,
+            crlf: false,
+            line_number: false,
+        };
+        let (matched, output) = search_str("1 22 x\nno digits\n333\n", &matcher);
+        assert!(matched);
+        assert_eq!(output, "1\n22\n333\n");
+    }
+
+    #[test]
+    fn test_replace_matches() {
+        let pattern = compile_pattern("(?<key>\\w+)=(?<value>\\w+)").unwrap();
+        let matcher = LineMatcher {
+            pattern: &pattern,
+            only_group: None,
+            replacement: Some("${value}:$1"),
+            crlf: false,
+            line_number: false,
+        };
+        let (matched, output) = search_str("a=1, b=2;\nnone\n", &matcher);
+        assert!(matched);
+        assert_eq!(output, "1:a, 2:b;\n");
+
+        let matcher = LineMatcher {
+            pattern: &pattern,
+            only_group: Some(0),
+            replacement: Some("${value}"),
+            crlf: false,
+            line_number: false,
+        };
+        let (matched, output) = search_str("a=1, b=2;\nnone\n", &matcher);
+        assert!(matched);
+        assert_eq!(output, "1\n2\n");
+    }
+}